@@ -0,0 +1,99 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+// the crate is a binary, so the bit grid is pulled in as a file; not every method gets used,
+// and its tests don't run from here
+#[allow(dead_code, unused_imports)]
+#[path = "../src/bitboard.rs"]
+mod bitboard;
+
+use bitboard::BitGrid;
+
+const SLOPES: [(usize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
+// like `aoc2020 scale -d 3`: rows as wide as the puzzle's, about one square in five a tree
+fn generate(rows: usize) -> String {
+    let mut state = 2020u64;
+    let mut out = String::with_capacity(rows * 32);
+    for _ in 0..rows {
+        for _ in 0..31 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            out.push(if state.is_multiple_of(5) { '#' } else { '.' });
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// how day 3 used to count: the lines as they are, a char lookup per square
+fn trees_by_chars(lines: &[&str], (right, down): (usize, usize)) -> usize {
+    lines
+        .iter()
+        .step_by(down)
+        .enumerate()
+        .filter(|(i, line)| line.chars().nth(i * right % line.len()) == Some('#'))
+        .count()
+}
+
+// then a bool per square in one flat Vec, as `Grid<bool>` keeps them
+fn trees_by_bools(cells: &[bool], width: usize, (right, down): (usize, usize)) -> usize {
+    (0..cells.len() / width)
+        .step_by(down)
+        .enumerate()
+        .filter(|&(i, row)| cells[row * width + i * right % width])
+        .count()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day3 five slopes");
+    group.sample_size(20);
+    for &rows in &[10_000, 1_000_000] {
+        let input = generate(rows);
+        let lines = input.lines().collect::<Vec<&str>>();
+        let cells = input
+            .bytes()
+            .filter(|&b| b != b'\n')
+            .map(|b| b == b'#')
+            .collect::<Vec<bool>>();
+        let grid = BitGrid::from_rows(lines.iter().map(|l| l.bytes().map(|b| b == b'#')));
+
+        let expected = SLOPES
+            .iter()
+            .map(|&s| trees_by_chars(&lines, s))
+            .collect::<Vec<usize>>();
+        assert_eq!(grid.count_steps((0, 0), &SLOPES), expected);
+
+        group.bench_with_input(BenchmarkId::new("chars", rows), &lines, |b, lines| {
+            b.iter(|| {
+                SLOPES
+                    .iter()
+                    .map(|&s| trees_by_chars(lines, s))
+                    .sum::<usize>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("bools", rows), &cells, |b, cells| {
+            b.iter(|| {
+                SLOPES
+                    .iter()
+                    .map(|&s| trees_by_bools(cells, 31, s))
+                    .sum::<usize>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("bits", rows), &grid, |b, grid| {
+            b.iter(|| {
+                SLOPES
+                    .iter()
+                    .map(|&s| grid.count_steps((0, 0), &[s])[0])
+                    .sum::<usize>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("bits together", rows), &grid, |b, grid| {
+            b.iter(|| grid.count_steps((0, 0), &SLOPES).iter().sum::<usize>())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
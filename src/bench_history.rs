@@ -0,0 +1,274 @@
+// Keeps per-day timings across runs (keyed by git commit) so slowdowns show up against the
+// last commit that was measured, instead of vanishing with criterion's target/ dir.
+use crate::inputs::Inputs;
+use crate::registry::{self, Day, Outcome};
+use crate::report::format_duration;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub commit: String,
+    pub timestamp: u64,
+    pub day: usize,
+    pub parse: Duration,
+    pub part1: Duration,
+    pub part2: Duration,
+}
+
+impl Record {
+    pub fn total(&self) -> Duration {
+        self.parse + self.part1 + self.part2
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.commit,
+            self.timestamp,
+            self.day,
+            self.parse.as_nanos(),
+            self.part1.as_nanos(),
+            self.part2.as_nanos()
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Record> {
+        let fields = line.split('\t').collect::<Vec<&str>>();
+        if let [commit, timestamp, day, parse, part1, part2] = fields[..] {
+            let nanos = |s: &str| s.parse::<u64>().ok().map(Duration::from_nanos);
+            Some(Record {
+                commit: String::from(commit),
+                timestamp: timestamp.parse().ok()?,
+                day: day.parse().ok()?,
+                parse: nanos(parse)?,
+                part1: nanos(part1)?,
+                part2: nanos(part2)?,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Comparison {
+    pub day: usize,
+    pub baseline: Duration,
+    pub current: Duration,
+    pub change_pct: f64,
+    pub regressed: bool,
+}
+
+pub fn parse_history(s: &str) -> Vec<Record> {
+    s.lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(Record::from_line)
+        .collect()
+}
+
+// the most recently measured commit other than `commit`, and its timings
+pub fn baseline<'a>(history: &'a [Record], commit: &str) -> Vec<&'a Record> {
+    let previous = history.iter().rev().find(|r| r.commit != commit);
+    match previous {
+        None => Vec::new(),
+        Some(previous) => {
+            let mut records: Vec<&Record> = Vec::new();
+            // latest record per day for that commit
+            for r in history.iter().rev().filter(|r| r.commit == previous.commit) {
+                if !records.iter().any(|seen| seen.day == r.day) {
+                    records.push(r);
+                }
+            }
+            records.sort_by_key(|r| r.day);
+            records
+        }
+    }
+}
+
+pub fn compare(baseline: &[&Record], current: &[Record], threshold_pct: f64) -> Vec<Comparison> {
+    current
+        .iter()
+        .filter_map(|now| {
+            let before = baseline.iter().find(|b| b.day == now.day)?;
+            let (b, c) = (before.total().as_secs_f64(), now.total().as_secs_f64());
+            let change_pct = if b > 0.0 { (c - b) / b * 100.0 } else { 0.0 };
+            Some(Comparison {
+                day: now.day,
+                baseline: before.total(),
+                current: now.total(),
+                change_pct,
+                regressed: change_pct > threshold_pct,
+            })
+        })
+        .collect()
+}
+
+// short hash, with "-dirty" appended when the working tree has changes
+pub fn current_commit() -> String {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    };
+    match git(&["rev-parse", "--short", "HEAD"]) {
+        None => String::from("unknown"),
+        Some(hash) => match git(&["status", "--porcelain"]) {
+            Some(status) if !status.is_empty() => format!("{}-dirty", hash),
+            _ => hash,
+        },
+    }
+}
+
+// fastest of `runs` runs, per phase
+fn measure(day: &Day, input: &str, runs: usize, commit: &str, timestamp: u64) -> Record {
+    let outcomes = (0..runs.max(1))
+        .map(|_| (day.solve)(input))
+        .collect::<Vec<Outcome>>();
+    let fastest = |t: fn(&Outcome) -> Duration| outcomes.iter().map(t).min().unwrap();
+    Record {
+        commit: String::from(commit),
+        timestamp,
+        day: day.day,
+        parse: fastest(|o| o.parse_time),
+        part1: fastest(|o| o.part1_time),
+        part2: fastest(|o| o.part2_time),
+    }
+}
+
+// Returns whether any day regressed by more than `threshold_pct`.
+pub fn bench_history(
+    inputs: &Inputs,
+    history_file: &str,
+    runs: usize,
+    threshold_pct: f64,
+    days: Option<Vec<usize>>,
+) -> io::Result<bool> {
+    let history = match fs::read_to_string(history_file) {
+        Ok(s) => parse_history(&s),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+
+    let commit = current_commit();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let current = registry::days()
+        .iter()
+        .filter(|d| days.as_ref().is_none_or(|days| days.contains(&d.day)))
+        .filter_map(|d| match inputs.get(d.day) {
+            Ok(input) => {
+                eprintln!("Timing day {}", d.day);
+                Some(measure(d, &input, runs, &commit, timestamp))
+            }
+            Err(e) => {
+                eprintln!("Skipping day {}: {}", d.day, e);
+                None
+            }
+        })
+        .collect::<Vec<Record>>();
+
+    let mut out = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(history_file)?;
+    if history.is_empty() {
+        writeln!(
+            out,
+            "# commit\ttimestamp\tday\tparse_ns\tpart1_ns\tpart2_ns"
+        )?;
+    }
+    for record in &current {
+        writeln!(out, "{}", record.to_line())?;
+    }
+
+    let baseline = baseline(&history, &commit);
+    match baseline.first() {
+        None => {
+            println!(
+                "Recorded {} days for {}; no earlier commit to compare against",
+                current.len(),
+                commit
+            );
+            return Ok(false);
+        }
+        Some(b) => println!("Comparing {} against {}", commit, b.commit),
+    }
+
+    let comparisons = compare(&baseline, &current, threshold_pct);
+    println!(
+        "{:>4} {:>12} {:>12} {:>8}",
+        "day", "baseline", "current", "change"
+    );
+    for c in &comparisons {
+        println!(
+            "{:>4} {:>12} {:>12} {:>+7.1}%{}",
+            c.day,
+            format_duration(c.baseline),
+            format_duration(c.current),
+            c.change_pct,
+            if c.regressed { "  REGRESSED" } else { "" }
+        );
+    }
+    Ok(comparisons.iter().any(|c| c.regressed))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(commit: &str, day: usize, millis: u64) -> Record {
+        Record {
+            commit: String::from(commit),
+            timestamp: 0,
+            day,
+            parse: Duration::from_millis(0),
+            part1: Duration::from_millis(millis),
+            part2: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let r = record("abc123", 17, 5);
+        assert_eq!(Record::from_line(&r.to_line()), Some(r));
+        assert_eq!(parse_history("# header\nbogus\n").len(), 0);
+    }
+
+    #[test]
+    fn test_baseline_skips_current_commit() {
+        let history = vec![
+            record("old", 1, 10),
+            record("prev", 1, 20),
+            record("prev", 2, 20),
+            record("prev", 1, 30),
+            record("now", 1, 99),
+        ];
+        let base = baseline(&history, "now");
+        assert_eq!(base.len(), 2);
+        assert_eq!(base[0].total(), Duration::from_millis(60)); // latest run of day 1 wins
+        assert!(baseline(&history[..1], "old").is_empty());
+    }
+
+    #[test]
+    fn test_compare() {
+        let before = [record("prev", 1, 10), record("prev", 2, 10)];
+        let base = before.iter().collect::<Vec<&Record>>();
+        let now = vec![
+            record("now", 1, 12),
+            record("now", 2, 10),
+            record("now", 3, 1),
+        ];
+        let comparisons = compare(&base, &now, 10.0);
+        assert_eq!(comparisons.len(), 2);
+        assert!(comparisons[0].regressed);
+        assert!((comparisons[0].change_pct - 20.0).abs() < 1e-9);
+        assert!(!comparisons[1].regressed);
+    }
+}
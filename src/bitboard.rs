@@ -0,0 +1,131 @@
+// A grid of bits packed 64 to a word, row by row, for maps too big to keep a bool per cell
+// (day 3's generated hills run to millions of rows). Each row starts on a fresh word, so a
+// lookup is a shift and a mask and a ride down the map reads memory front to back.
+//
+// This file has no crate dependencies so `benches/day3_trees.rs` can include it as it is.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    // no rows yet; `push_row` adds them
+    pub fn new(width: usize) -> Self {
+        BitGrid {
+            width,
+            height: 0,
+            words_per_row: width.div_ceil(64),
+            words: Vec::new(),
+        }
+    }
+
+    // one row per item, `true` for a set bit; rows shorter than the first are padded with unset
+    // bits and longer ones cut. Day 3 checks its rows as it goes and pushes them itself, so
+    // this is for the tests and the bench.
+    #[allow(dead_code)]
+    pub fn from_rows<R, I>(rows: R) -> Self
+    where
+        R: IntoIterator<Item = I>,
+        I: IntoIterator<Item = bool>,
+    {
+        let mut rows = rows.into_iter();
+        let first = rows
+            .next()
+            .map(|r| r.into_iter().collect::<Vec<bool>>())
+            .unwrap_or_default();
+        let mut grid = BitGrid::new(first.len());
+        grid.push_row(first);
+        rows.for_each(|r| grid.push_row(r));
+        grid
+    }
+
+    // `true` for a set bit; a short row is padded with unset bits and a long one cut
+    pub fn push_row(&mut self, bits: impl IntoIterator<Item = bool>) {
+        let start = self.words.len();
+        self.words.resize(start + self.words_per_row, 0);
+        for (col, bit) in bits.into_iter().take(self.width).enumerate() {
+            self.words[start + col / 64] |= (bit as u64) << (col % 64);
+        }
+        self.height += 1;
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // the grid repeats to the right, so any column will do; the row must be on the grid
+    pub fn get_wrapped(&self, row: usize, col: usize) -> bool {
+        let col = col % self.width;
+        self.words[row * self.words_per_row + col / 64] >> (col % 64) & 1 == 1
+    }
+
+    // Set bits landed on by each (right, down) step from `start` to the bottom edge, columns
+    // wrapping. The map is walked a block of rows at a time, every step going through the
+    // block before the next, so a block is read from memory once however many steps there are.
+    pub fn count_steps(&self, start: (usize, usize), steps: &[(usize, usize)]) -> Vec<usize> {
+        const BLOCK_ROWS: usize = 4096;
+        let mut counts = vec![0; steps.len()];
+        if self.width == 0 {
+            return counts;
+        }
+        let mut squares = vec![(start.0, start.1 % self.width); steps.len()];
+        let mut end = start.0;
+        while end < self.height {
+            end = (end + BLOCK_ROWS).min(self.height);
+            for (i, &(right, down)) in steps.iter().enumerate() {
+                let right = right % self.width; // so one subtraction wraps it
+                let (mut row, mut col) = squares[i];
+                let mut count = 0;
+                while row < end {
+                    let word = self.words[row * self.words_per_row + col / 64];
+                    count += (word >> (col % 64) & 1) as usize;
+                    row += down;
+                    col += right;
+                    if col >= self.width {
+                        col -= self.width;
+                    }
+                }
+                squares[i] = (row, col);
+                counts[i] += count;
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bits() {
+        // wider than a word, so rows take two
+        let bits = |cols: &'static [usize]| (0..70).map(move |c| cols.contains(&c));
+        let grid = BitGrid::from_rows(vec![bits(&[0]), bits(&[63, 64]), bits(&[69])]);
+        assert!(grid.get_wrapped(0, 0) && grid.get_wrapped(0, 70));
+        assert!(grid.get_wrapped(1, 63) && grid.get_wrapped(1, 64));
+        assert!(!grid.get_wrapped(1, 65) && !grid.get_wrapped(2, 0));
+        assert!(grid.get_wrapped(2, 139));
+
+        let rows = ["#..", ".#.", "..#", "#.."];
+        let grid = BitGrid::from_rows(rows.iter().map(|r| r.chars().map(|c| c == '#')));
+        assert_eq!((grid.height(), grid.width()), (4, 3));
+        assert_eq!(
+            grid.count_steps((0, 0), &[(1, 1), (2, 1), (1, 2), (0, 3)]),
+            [4, 2, 1, 2]
+        );
+        assert_eq!(grid.count_steps((1, 1), &[(1, 1)]), [3]);
+
+        let ragged = BitGrid::from_rows(vec![vec![true, false], vec![false, true, true], vec![]]);
+        assert_eq!((ragged.height(), ragged.width()), (3, 2));
+        assert_eq!(ragged.count_steps((0, 0), &[(1, 1), (2, 1)]), [2, 1]);
+    }
+}
@@ -0,0 +1,199 @@
+// Answers from earlier runs, so regenerating a report doesn't mean replaying day 15's 30M turns.
+// Entries are keyed by day, part and a hash of the input along with any settings the answers
+// depend on (see `registry::settings`), and only count for the build that wrote them: the build
+// id is a hash of the running binary, so rebuilding throws them all away.
+use crate::registry::Outcome;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_FILE: &str = ".aoc-cache";
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    parse: Duration,
+    time: Duration,
+    answer: String,
+}
+
+pub struct Cache {
+    path: PathBuf,
+    build: String,
+    entries: HashMap<(usize, usize, u64), Entry>, // (day, part, input and settings hash)
+}
+
+// FNV-1a, which unlike std's hasher gives the same answer on every platform and toolchain
+pub fn hash(bytes: &[u8]) -> u64 {
+    extend_hash(0xcbf2_9ce4_8422_2325, bytes)
+}
+
+fn extend_hash(hash: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(hash, |h, &b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+// the input alone when there are no settings, so most days' keys are plain input hashes
+fn key_hash(input: &str, settings: &str) -> u64 {
+    let input = hash(input.as_bytes());
+    match settings {
+        "" => input,
+        _ => extend_hash(extend_hash(input, &[0]), settings.as_bytes()),
+    }
+}
+
+// changes whenever the binary does
+pub fn build_id() -> io::Result<String> {
+    let exe = fs::read(env::current_exe()?)?;
+    Ok(format!("{:016x}", hash(&exe)))
+}
+
+impl Cache {
+    // the cache at `path` as seen by this binary; a missing file is just an empty cache
+    pub fn open(path: &Path) -> io::Result<Cache> {
+        Cache::load(path, &build_id()?)
+    }
+
+    fn load(path: &Path, build: &str) -> io::Result<Cache> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let entries = text
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .filter_map(|l| {
+                let fields = l.splitn(7, '\t').collect::<Vec<&str>>();
+                match fields[..] {
+                    [b, day, part, input, parse, time, answer] if b == build => {
+                        let nanos = |s: &str| s.parse::<u64>().ok().map(Duration::from_nanos);
+                        let key = (
+                            day.parse().ok()?,
+                            part.parse().ok()?,
+                            u64::from_str_radix(input, 16).ok()?,
+                        );
+                        let entry = Entry {
+                            parse: nanos(parse)?,
+                            time: nanos(time)?,
+                            answer: String::from(answer),
+                        };
+                        Some((key, entry))
+                    }
+                    _ => None, // malformed, or from another build
+                }
+            })
+            .collect();
+        Ok(Cache {
+            path: path.to_path_buf(),
+            build: String::from(build),
+            entries,
+        })
+    }
+
+    // a day counts as cached once its part 1 answer is; part 2 may be unsolved
+    pub fn get(&self, day: usize, input: &str, settings: &str) -> Option<Outcome> {
+        let input = key_hash(input, settings);
+        let part1 = self.entries.get(&(day, 1, input))?;
+        let part2 = self.entries.get(&(day, 2, input));
+        Some(Outcome {
+            part1: Some(part1.answer.clone()),
+            part2: part2.map(|e| e.answer.clone()),
+            parse_time: part1.parse,
+            part1_time: part1.time,
+            part2_time: part2.map_or(Duration::default(), |e| e.time),
+        })
+    }
+
+    pub fn insert(&mut self, day: usize, input: &str, settings: &str, outcome: &Outcome) {
+        let input = key_hash(input, settings);
+        let parts = [
+            (1, &outcome.part1, outcome.part1_time),
+            (2, &outcome.part2, outcome.part2_time),
+        ];
+        for (part, answer, time) in parts.iter() {
+            // answers are single tokens, but don't let a stray newline break the file
+            if let Some(answer) = answer.as_ref().filter(|a| !a.contains('\n')) {
+                let entry = Entry {
+                    parse: outcome.parse_time,
+                    time: *time,
+                    answer: answer.clone(),
+                };
+                self.entries.insert((day, *part, input), entry);
+            }
+        }
+    }
+
+    // rewrites the file with only this build's entries
+    pub fn save(&self) -> io::Result<()> {
+        let mut keys = self.entries.keys().collect::<Vec<_>>();
+        keys.sort();
+        let mut out = String::from("# build\tday\tpart\tinput_hash\tparse_ns\tpart_ns\tanswer\n");
+        for key in keys {
+            let (day, part, input) = key;
+            let e = &self.entries[key];
+            out += &format!(
+                "{}\t{}\t{}\t{:016x}\t{}\t{}\t{}\n",
+                self.build,
+                day,
+                part,
+                input,
+                e.parse.as_nanos(),
+                e.time.as_nanos(),
+                e.answer
+            );
+        }
+        fs::write(&self.path, out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn outcome(part2: Option<&str>) -> Outcome {
+        Outcome {
+            part1: Some(String::from("514579")),
+            part2: part2.map(String::from),
+            parse_time: Duration::from_micros(3),
+            part1_time: Duration::from_millis(2),
+            part2_time: Duration::from_millis(7),
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        // reference values for 64-bit FNV-1a
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = env::temp_dir().join(format!("aoc-cache-test-{}", std::process::id()));
+        let mut cache = Cache::load(&path, "build1").unwrap();
+        assert_eq!(cache.get(1, "1721\n979\n", ""), None);
+        cache.insert(1, "1721\n979\n", "", &outcome(Some("241861950")));
+        cache.insert(19, "0: 1\n", "", &outcome(None));
+        cache.insert(4, "byr:1\n", "byr: any\n", &outcome(None));
+        cache.save().unwrap();
+
+        let cache = Cache::load(&path, "build1").unwrap();
+        assert_eq!(
+            cache.get(1, "1721\n979\n", ""),
+            Some(outcome(Some("241861950")))
+        );
+        assert_eq!(cache.get(19, "0: 1\n", "").unwrap().part2, None);
+        assert!(cache.get(4, "byr:1\n", "byr: any\n").is_some());
+        // a different input, different settings or a different binary misses
+        assert_eq!(cache.get(1, "1721\n980\n", ""), None);
+        assert_eq!(cache.get(4, "byr:1\n", "byr: range 0-9\n"), None);
+        assert_eq!(cache.get(4, "byr:1\n", ""), None);
+        let other_build = Cache::load(&path, "build2").unwrap();
+        assert_eq!(other_build.get(1, "1721\n979\n", ""), None);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,430 @@
+// The command line, in one place so `completions` and `manpage` describe exactly what `main`
+// parses.
+use crate::day2;
+use crate::registry;
+use crate::scale;
+use clap::{App, Arg, Shell, SubCommand};
+use std::io::{self, Write};
+
+lazy_static! {
+    static ref DAYS: Vec<String> = registry::days().iter().map(|d| d.day.to_string()).collect();
+    static ref SCALE_DAYS: Vec<String> = scale::generators()
+        .iter()
+        .map(|g| g.day.to_string())
+        .collect();
+    static ref POLICY_HELP: String = format!(
+        "day 2: a password policy to check, repeatable (default count and position): {}",
+        day2::POLICY_HELP
+    );
+}
+
+const VERSION: &str = "1.0.0";
+
+fn names(days: &'static [String]) -> Vec<&'static str> {
+    days.iter().map(String::as_str).collect()
+}
+
+// a whole number of at least 1
+fn positive(value: String) -> Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(n) if n >= 1 => Ok(()),
+        _ => Err(format!(
+            "expected a whole number of at least 1, not {:?}",
+            value
+        )),
+    }
+}
+
+pub fn cli() -> App<'static, 'static> {
+    App::new("Advent of Code 2020")
+        .version(VERSION)
+        .author("Mike Erickson <mike.erickson@gmail.com>")
+        .arg(
+            Arg::with_name("day")
+                .short("d")
+                .long("day")
+                .takes_value(true)
+                .possible_values(&names(&DAYS))
+                .help("day or exercise"),
+        )
+        .arg(
+            Arg::with_name("target")
+                .long("target")
+                .takes_value(true)
+                .default_value("2020")
+                .allow_hyphen_values(true)
+                .help("day 1: the sum to look for"),
+        )
+        .arg(
+            Arg::with_name("k")
+                .long("k")
+                .takes_value(true)
+                .use_delimiter(true)
+                .help("day 1: how many numbers to add up, e.g. 4 or 2,3 (default 2,3)"),
+        )
+        .arg(
+            Arg::with_name("all")
+                .long("all")
+                .help("day 1: list every combination, not just the first"),
+        )
+        .arg(
+            Arg::with_name("count")
+                .long("count")
+                .conflicts_with("all")
+                .help("day 1: only count the combinations (fast on huge lists)"),
+        )
+        .arg(
+            Arg::with_name("policy")
+                .long("policy")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help(&POLICY_HELP),
+        )
+        .arg(
+            Arg::with_name("verdicts")
+                .long("verdicts")
+                .help("day 2: show each line's verdict under every policy"),
+        )
+        .arg(
+            Arg::with_name("generate")
+                .long("generate")
+                .takes_value(true)
+                .value_name("LINES")
+                .help("day 2: print a made-up input, a quarter of it per pass/fail combination"),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .default_value("2020")
+                .help("seed for --generate"),
+        )
+        .arg(
+            Arg::with_name("slope")
+                .long("slope")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("RIGHT,DOWN")
+                .help("day 3: a slope to ride, repeatable (default the puzzle's five)"),
+        )
+        .arg(
+            Arg::with_name("optimise")
+                .long("optimise")
+                .takes_value(true)
+                .value_name("K")
+                .help("day 3: try every slope and find the best and worst product of K of them"),
+        )
+        .arg(
+            Arg::with_name("start")
+                .long("start")
+                .takes_value(true)
+                .value_name("ROW,COL")
+                .help("day 3: where rides start, counting from 0,0 at the top left"),
+        )
+        .arg(
+            Arg::with_name("wrap-vertically")
+                .long("wrap-vertically")
+                .help("day 3: carry on from the top after the bottom, until a square repeats"),
+        )
+        .arg(
+            Arg::with_name("draw")
+                .long("draw")
+                .takes_value(true)
+                .min_values(0)
+                .max_values(1)
+                .value_name("FILE")
+                .help("day 3: draw the slopes over the map, to stdout or FILE"),
+        )
+        .arg(
+            Arg::with_name("marks")
+                .long("marks")
+                .takes_value(true)
+                .default_value("OX")
+                .help("day 3 --draw: open and tree marks per slope, e.g. OX,ox,@%"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .help("day 3 --draw: colour each slope's marks"),
+        )
+        .arg(
+            Arg::with_name("schema")
+                .long("schema")
+                .takes_value(true)
+                .value_name("FILE")
+                .help(
+                    "day 4: passport rules to check instead of the puzzle's (default \
+                     passports.schema in the input directory, if it's there)",
+                ),
+        )
+        .arg(
+            Arg::with_name("export")
+                .long("export")
+                .takes_value(true)
+                .value_name("DIR")
+                .help("also export grid states as images into DIR (days 3, 11 and 17)"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["png", "ppm"])
+                .default_value("png")
+                .help("image format for --export"),
+        )
+        .arg(
+            Arg::with_name("scale")
+                .long("scale")
+                .takes_value(true)
+                .default_value("4")
+                .validator(positive)
+                .help("pixels per grid cell for --export"),
+        )
+        .arg(
+            Arg::with_name("input-dir")
+                .long("input-dir")
+                .takes_value(true)
+                .value_name("DIR")
+                .env("AOC_INPUT_DIR")
+                .help("read inputs from DIR/<day>.input instead of the default ones"),
+        )
+        .arg(
+            Arg::with_name("profile-phases")
+                .long("profile-phases")
+                .help("print a timing tree of each phase instead (all days unless --day is given)"),
+        )
+        .subcommands(subcommands())
+}
+
+fn subcommands() -> Vec<App<'static, 'static>> {
+    let subcommands = vec![
+        SubCommand::with_name("detect")
+            .about("guesses which day an input file belongs to")
+            .arg(
+                Arg::with_name("file")
+                    .required(true)
+                    .help("the input file to look at"),
+            ),
+        SubCommand::with_name("scale")
+            .about("times a day's solver on generated inputs of growing size")
+            .arg(
+                Arg::with_name("day")
+                    .long("day")
+                    .short("d")
+                    .takes_value(true)
+                    .required(true)
+                    .possible_values(&names(&SCALE_DAYS))
+                    .help("day to probe"),
+            )
+            .arg(
+                Arg::with_name("sizes")
+                    .long("sizes")
+                    .takes_value(true)
+                    .use_delimiter(true)
+                    .help("input sizes to try, e.g. 100,200,400 (default depends on the day)"),
+            )
+            .arg(
+                Arg::with_name("seed")
+                    .long("seed")
+                    .takes_value(true)
+                    .default_value("2020")
+                    .help("seed for the input generator"),
+            )
+            .arg(
+                Arg::with_name("runs")
+                    .long("runs")
+                    .takes_value(true)
+                    .default_value("3")
+                    .help("time each size this many times and keep the fastest"),
+            ),
+        SubCommand::with_name("leaderboard")
+            .about("stats for a private leaderboard from its exported JSON")
+            .arg(
+                Arg::with_name("file")
+                    .required(true)
+                    .help("the JSON file saved from the leaderboard's [API] link"),
+            )
+            .arg(
+                Arg::with_name("json")
+                    .long("json")
+                    .help("print the stats as JSON instead of tables"),
+            ),
+        SubCommand::with_name("report")
+            .about("runs every day and writes a report of answers, timings and stars")
+            .arg(
+                Arg::with_name("output")
+                    .long("output")
+                    .short("o")
+                    .takes_value(true)
+                    .default_value("report.md")
+                    .help("Markdown file to write"),
+            )
+            .arg(
+                Arg::with_name("no-cache")
+                    .long("no-cache")
+                    .help("rerun every day instead of reusing answers cached for this build"),
+            )
+            .arg(
+                Arg::with_name("html")
+                    .long("html")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help("also write the report as HTML"),
+            )
+            .arg(
+                Arg::with_name("answers")
+                    .long("answers")
+                    .takes_value(true)
+                    .default_value("answers.txt")
+                    .help("known answers to check against, as `day part answer` lines"),
+            ),
+        SubCommand::with_name("completions")
+            .about("prints a shell completion script")
+            .arg(
+                Arg::with_name("shell")
+                    .required(true)
+                    .possible_values(&Shell::variants())
+                    .help("shell to complete for"),
+            ),
+        SubCommand::with_name("manpage").about("prints a man page for this command"),
+    ];
+    #[cfg(feature = "bench-history")]
+    let subcommands = subcommands
+        .into_iter()
+        .chain(Some(bench_history()))
+        .collect();
+    subcommands
+}
+
+#[cfg(feature = "bench-history")]
+fn bench_history() -> App<'static, 'static> {
+    SubCommand::with_name("bench-history")
+        .about("times every day, appends to a history file and flags regressions")
+        .arg(
+            Arg::with_name("history")
+                .long("history")
+                .takes_value(true)
+                .default_value(".bench-history")
+                .help("history file, one line per day per run"),
+        )
+        .arg(
+            Arg::with_name("runs")
+                .long("runs")
+                .takes_value(true)
+                .default_value("3")
+                .help("runs per day; the fastest is recorded"),
+        )
+        .arg(
+            Arg::with_name("threshold")
+                .long("threshold")
+                .takes_value(true)
+                .default_value("10")
+                .help("percent slowdown against the previous commit that counts as a regression"),
+        )
+        .arg(
+            Arg::with_name("days")
+                .long("days")
+                .takes_value(true)
+                .use_delimiter(true)
+                .help("only time these days, e.g. 15,17"),
+        )
+}
+
+pub fn completions<W: Write>(shell: &str, out: &mut W) -> Result<(), String> {
+    let shell = shell.parse::<Shell>()?;
+    cli().gen_completions_to(env!("CARGO_PKG_NAME"), shell, out);
+    Ok(())
+}
+
+// help text can't be taken as roff as-is: backslashes and lines starting with '.' or '\''
+// mean something to it
+fn roff_text(text: &str) -> String {
+    text.lines()
+        .map(|l| {
+            let l = l.replace('\\', "\\e");
+            if l.starts_with('.') || l.starts_with('\'') {
+                format!("\\&{}", l)
+            } else {
+                l
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn long_help(mut app: App) -> io::Result<String> {
+    let mut help = Vec::new();
+    app.write_long_help(&mut help)
+        .map_err(|e| io::Error::other(e.message))?;
+    Ok(String::from_utf8_lossy(&help).into_owned())
+}
+
+// the top-level help, then a section per subcommand, each kept as clap lays it out
+pub fn manpage<W: Write>(out: &mut W) -> io::Result<()> {
+    let bin = env!("CARGO_PKG_NAME");
+    let app = cli().bin_name(bin);
+    writeln!(
+        out,
+        ".TH {} 1 \"\" \"{} {}\"",
+        bin.to_uppercase(),
+        bin,
+        VERSION
+    )?;
+    writeln!(out, ".SH NAME\n{} \\- {}", bin, roff_text(app.get_name()))?;
+    writeln!(
+        out,
+        ".SH DESCRIPTION\n.nf\n{}\n.fi",
+        roff_text(&long_help(app)?)
+    )?;
+    writeln!(out, ".SH SUBCOMMANDS")?;
+    for sub in subcommands() {
+        let name = String::from(sub.get_name());
+        let sub = sub.bin_name(format!("{} {}", bin, name));
+        writeln!(
+            out,
+            ".SS {}\n.nf\n{}\n.fi",
+            name,
+            roff_text(&long_help(sub)?)
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_completions() {
+        let mut script = Vec::new();
+        completions("bash", &mut script).unwrap();
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("manpage"));
+        assert!(script.contains("1 2 3"), "days from the registry");
+        assert!(completions("tcsh", &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_scale() {
+        let parse = |scale: &str| cli().get_matches_from_safe(vec!["aoc", "--scale", scale]);
+        assert_eq!(parse("2").unwrap().value_of("scale"), Some("2"));
+        for bad in &["0", "00", "x"] {
+            let error = parse(bad).err().unwrap().message;
+            assert!(error.contains("at least 1"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_manpage() {
+        let mut page = Vec::new();
+        manpage(&mut page).unwrap();
+        let page = String::from_utf8(page).unwrap();
+        assert!(page.starts_with(".TH AOC2020 1"));
+        for sub in subcommands() {
+            assert!(page.contains(&format!(".SS {}\n", sub.get_name())));
+        }
+        assert_eq!(roff_text(".hidden\\"), "\\&.hidden\\e");
+    }
+}
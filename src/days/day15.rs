@@ -1,15 +1,17 @@
+use crate::parse;
+use crate::registry::{self, Outcome};
 use std::collections::HashMap;
 
-pub fn day15() {
+pub fn day15(input: &str) {
     let turn_count1 = 2020;
-    let gen1 = GameNumGen::new(vec![20, 9, 11, 0, 1, 2]);
+    let gen1 = GameNumGen::new(parse(input));
     println!(
         "On turn {} the number {} will be spoken",
         turn_count1,
         &gen1.take(turn_count1).last().unwrap()
     );
 
-    let gen2 = GameNumGen::new(vec![20, 9, 11, 0, 1, 2]);
+    let gen2 = GameNumGen::new(parse(input));
     let turn_count2 = 30000000;
 
     println!(
@@ -19,6 +21,29 @@ pub fn day15() {
     );
 }
 
+pub fn solve(input: &str) -> Outcome {
+    registry::run(input, parse, part1, Some(part2))
+}
+
+fn part1(starting_nums: &[usize]) -> String {
+    spoken_on(starting_nums, 2020).to_string()
+}
+
+fn part2(starting_nums: &[usize]) -> String {
+    spoken_on(starting_nums, 30000000).to_string()
+}
+
+fn parse(input: &str) -> Vec<usize> {
+    parse::list(input, ',').unwrap_or_else(|e| panic!("day 15 input: {}", e))
+}
+
+fn spoken_on(starting_nums: &[usize], turn: usize) -> usize {
+    GameNumGen::new(starting_nums.to_vec())
+        .take(turn)
+        .last()
+        .unwrap()
+}
+
 #[derive(Debug)]
 struct GameNumGen {
     turn: usize, // 1-indexed!
@@ -1,26 +1,62 @@
+use crate::parse;
+use crate::profile;
+use crate::registry::{self, Outcome};
+use crate::scale::Rng;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
-pub fn day16() {
-    let (constraints, my_ticket, mut tickets) = parse_input(include_str!("16.input")).unwrap();
+type Notes = (Vec<FieldConstraint>, Ticket, Vec<Ticket>);
+
+pub fn day16(input: &str) {
+    let (constraints, my_ticket, mut tickets) = parse_or_die(input);
 
     println!(
         "The invalid fields sum to {}",
         find_invalid_ticket_field_sum(&mut tickets, &constraints)
     );
 
-    tickets.retain(|t| t.invalid_ticket_values(&constraints).is_empty());
-    tickets
-        .iter_mut()
-        .for_each(|t| t.assign_field_labels(&constraints));
+    let (product, i) = departure_product(&constraints, &my_ticket, tickets);
+    println!("solved after {} iterations", i);
+    println!("The product of my departure fields is {}", product);
+}
+
+pub fn solve(input: &str) -> Outcome {
+    registry::run(input, parse_or_die, part1, Some(part2))
+}
+
+fn part1(notes: &Notes) -> String {
+    find_invalid_ticket_field_sum(&mut notes.2.clone(), &notes.0).to_string()
+}
+
+fn part2(notes: &Notes) -> String {
+    departure_product(&notes.0, &notes.1, notes.2.clone())
+        .0
+        .to_string()
+}
+
+// (product of my departure fields, refinement iterations it took)
+fn departure_product(
+    constraints: &Vec<FieldConstraint>,
+    my_ticket: &Ticket,
+    mut tickets: Vec<Ticket>,
+) -> (usize, usize) {
+    {
+        let _span = profile::span("discard invalid tickets");
+        tickets.retain(|t| t.invalid_ticket_values(constraints).is_empty());
+    }
+    {
+        let _span = profile::span("assign_field_labels");
+        tickets
+            .iter_mut()
+            .for_each(|t| t.assign_field_labels(constraints));
+    }
 
     let mut i = 0;
     while !tickets.iter().all(|t| t.is_valid()) {
+        let _span = profile::span("refine_ticket_labels");
         refine_ticket_labels(&mut tickets);
-        print!(".");
         i += 1;
     }
-    println!("\nsolved after {} iterations", i);
 
     let final_labels = tickets.get(0).unwrap().get_first_field_labels();
 
@@ -30,8 +66,7 @@ pub fn day16() {
             product *= my_ticket.fields.get(i).unwrap().0;
         }
     }
-
-    println!("The product of my departure fields is {}", product);
+    (product, i)
 }
 
 fn find_invalid_ticket_field_sum(
@@ -56,25 +91,79 @@ fn find_invalid_ticket_field_sum(
  * concern: possible ambiguity in which fields are invalid?
  */
 
-#[derive(Debug)]
-struct ParseInputError;
-fn parse_input(s: &str) -> Result<(Vec<FieldConstraint>, Ticket, Vec<Ticket>), ParseInputError> {
-    let mut constraints: Vec<FieldConstraint> = Vec::new();
-    let mut tickets: Vec<Ticket> = Vec::new();
-    let lines = s.lines().collect::<Vec<&str>>();
-    let mut i = 0;
-    while lines[i] != "" {
-        constraints.push(lines[i].parse::<FieldConstraint>().unwrap());
-        i += 1;
+fn parse_input(s: &str) -> parse::Result<Notes> {
+    let _span = profile::span("parse_input");
+    let sections = parse::sections(s, 3)?;
+    let constraints = {
+        let _span = profile::span("constraints");
+        sections[0]
+            .iter()
+            .map(|l| l.parse::<FieldConstraint>())
+            .collect::<parse::Result<Vec<FieldConstraint>>>()?
+    };
+    let my_ticket = match parse::body(&sections[1], "your ticket:")? {
+        [line] => line.parse::<Ticket>()?,
+        lines => {
+            let line = lines.get(1).unwrap_or(&sections[1][0]);
+            return Err(line.error(line.text, "expected exactly one ticket"));
+        }
+    };
+    let _span = profile::span("nearby tickets");
+    let tickets = parse::body(&sections[2], "nearby tickets:")?
+        .iter()
+        .map(|l| l.parse::<Ticket>())
+        .collect::<parse::Result<Vec<Ticket>>>()?;
+    Ok((constraints, my_ticket, tickets))
+}
+
+fn parse_or_die(s: &str) -> Notes {
+    parse_input(s).unwrap_or_else(|e| panic!("day 16 input: {}", e))
+}
+
+// `size` nearby tickets with 20 fields in a staircase: field j's column only holds values up to
+// 50·(j + 1), and the first ticket reaches that, so the column fits fields j.. and refining
+// settles them one at a time. About one ticket in ten has a value that fits no field.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    const FIELDS: usize = 20;
+    let mut out = String::new();
+    for j in 0..FIELDS {
+        let label = match j {
+            0..=5 => format!("departure {}", j),
+            _ => format!("field {}", j),
+        };
+        out += &format!("{}: 1-{} or 5000-{}\n", label, 50 * (j + 1), 5000 + j);
     }
-    i += 2;
-    let my_ticket = lines[i].parse::<Ticket>().unwrap();
-    i += 3;
-    while i < lines.len() {
-        tickets.push(lines[i].parse::<Ticket>().unwrap());
-        i += 1;
+
+    let mut columns = (0..FIELDS).collect::<Vec<usize>>();
+    rng.shuffle(&mut columns);
+    let ticket = |rng: &mut Rng, highest: bool| {
+        let mut values = columns
+            .iter()
+            .map(|&j| {
+                if highest {
+                    50 * (j + 1)
+                } else {
+                    rng.range(1, 50 * (j + 1) + 1)
+                }
+            })
+            .collect::<Vec<usize>>();
+        if !highest && rng.chance(10) {
+            values[rng.range(0, FIELDS)] = 9999;
+        }
+        values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    };
+
+    out += &format!("\nyour ticket:\n{}\n\nnearby tickets:\n", ticket(rng, true));
+    out += &ticket(rng, true);
+    for _ in 1..size {
+        out += "\n";
+        out += &ticket(rng, false);
     }
-    Ok((constraints, my_ticket, tickets))
+    out
 }
 
 // wee woo wee woo bad code
@@ -125,26 +214,22 @@ struct ConstraintParseError;
 impl FromStr for FieldConstraint {
     type Err = ConstraintParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut ranges: Vec<(usize, usize)> = Vec::new();
-        if let Some(colon_pos) = s.find(':') {
-            let label = String::from(&s[0..colon_pos]);
-            for range_str in s[colon_pos + 2..].split(" or ") {
-                let dash_pos = range_str.find('-').unwrap();
-                let lb = range_str
-                    .get(0..dash_pos)
-                    .unwrap()
-                    .parse::<usize>()
-                    .unwrap();
-                let ub = range_str
-                    .get(dash_pos + 1..)
-                    .unwrap()
-                    .parse::<usize>()
-                    .unwrap();
-                ranges.push((lb, ub));
-            }
-            return Ok(FieldConstraint { label, ranges });
-        }
-        return Err(ConstraintParseError);
+        let (label, ranges) = s.split_once(':').ok_or(ConstraintParseError)?;
+        let ranges = ranges
+            .trim()
+            .split(" or ")
+            .map(|range_str| {
+                let (lb, ub) = range_str.split_once('-').ok_or(ConstraintParseError)?;
+                match (lb.parse::<usize>(), ub.parse::<usize>()) {
+                    (Ok(lb), Ok(ub)) => Ok((lb, ub)),
+                    _ => Err(ConstraintParseError),
+                }
+            })
+            .collect::<Result<Vec<(usize, usize)>, ConstraintParseError>>()?;
+        Ok(FieldConstraint {
+            label: String::from(label),
+            ranges,
+        })
     }
 }
 
@@ -221,8 +306,9 @@ impl FromStr for Ticket {
         Ok(Ticket {
             fields: s
                 .split(',')
-                .map(|vs| (vs.parse::<usize>().unwrap(), HashSet::new()))
-                .collect::<Vec<(usize, HashSet<String>)>>(),
+                .map(|vs| vs.parse::<usize>().map(|v| (v, HashSet::new())))
+                .collect::<Result<Vec<(usize, HashSet<String>)>, _>>()
+                .map_err(|_| TicketParseError)?,
         })
     }
 }
@@ -273,9 +359,18 @@ nearby tickets:
         assert_eq!(parsed_input.2.len(), 4); // 4 other tickets
     }
 
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_input(&INPUT1.replace("14\n", "x\n")).unwrap_err();
+        assert_eq!(err.line, 6);
+        assert!(parse_input(&INPUT1.replace("nearby", "other")).is_err());
+        assert!(parse_input("class: 1-3 or 5-7").is_err());
+    }
+
     #[test]
     fn test_part_1() {
-        let (constraints, _, mut tickets) = parse_input(include_str!("16.input")).unwrap();
+        let (constraints, _, mut tickets) =
+            parse_input(include_str!("../../inputs/16.input")).unwrap();
         assert_eq!(
             find_invalid_ticket_field_sum(&mut tickets, &constraints),
             21071
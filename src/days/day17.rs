@@ -1,8 +1,11 @@
-use std::collections::HashMap;
+use crate::grid::Grid;
+use crate::registry::{self, Outcome};
+use crate::render::{self, GridRender, ImageFormat, Rgb};
+use std::path::Path;
 use std::str::FromStr;
 
-pub fn day17() {
-    let mut space = include_str!("17.input").parse::<Space>().unwrap();
+pub fn day17(input: &str) {
+    let mut space = input.parse::<Space>().unwrap();
     match space.step_times(6) {
         Err(_) => eprintln!("Error."),
         Ok(_) => {
@@ -13,7 +16,7 @@ pub fn day17() {
         }
     };
 
-    let mut space = include_str!("17.input").parse::<Space4D>().unwrap();
+    let mut space = input.parse::<Space4D>().unwrap();
     match space.step_times(6) {
         Err(_) => eprintln!("Error."),
         Ok(_) => {
@@ -25,19 +28,59 @@ pub fn day17() {
     };
 }
 
+pub fn solve(input: &str) -> Outcome {
+    registry::run(input, parse, part1, Some(part2))
+}
+
+fn part1(spaces: &(Space, Space4D)) -> String {
+    let mut space = spaces.0.clone();
+    space.step_times(6).unwrap();
+    space.active_count().to_string()
+}
+
+fn part2(spaces: &(Space, Space4D)) -> String {
+    let mut space = spaces.1.clone();
+    space.step_times(6).unwrap();
+    space.active_count().to_string()
+}
+
+fn parse(input: &str) -> (Space, Space4D) {
+    (
+        input.parse::<Space>().unwrap(),
+        input.parse::<Space4D>().unwrap(),
+    )
+}
+
 // I _could_ make space parametric over its dimension, but that sounds like work
 type Point4D = (isize, isize, isize, isize);
 
-#[derive(PartialEq)]
+// Cubes run -size..=size along every axis. Each (z, w) layer is a grid indexed by (x, y).
+#[derive(PartialEq, Clone, Debug)]
 struct Space4D {
     size: isize,
-    inner: HashMap<Point4D, bool>,
+    layers: Vec<Vec<Grid<bool>>>, // [w][z]
 }
 
 #[derive(Debug)]
 struct Space4DStepError;
 
 impl Space4D {
+    fn empty(size: isize) -> Self {
+        let side = (2 * size + 1) as usize;
+        Space4D {
+            size,
+            layers: vec![vec![Grid::new(side, side, false); side]; side],
+        }
+    }
+
+    fn get(&self, (x, y, z, w): Point4D) -> bool {
+        let i = |v: isize| axis_index(v, self.size);
+        match (i(x), i(y), i(z), i(w)) {
+            (Some(x), Some(y), Some(z), Some(w)) => self.layers[w][z][(x, y)],
+            _ => false,
+        }
+    }
+
     pub fn step_times(self: &mut Self, count: usize) -> Result<(), SpaceStepError> {
         for _ in 0..count {
             self.step()?;
@@ -45,14 +88,14 @@ impl Space4D {
         Ok(())
     }
 
+    // the active region can only grow by one cube in each direction per step
     pub fn step(self: &mut Self) -> Result<(), SpaceStepError> {
-        let mut new_inner: HashMap<Point4D, bool> = HashMap::new();
-        let lb = -self.size - 1;
-        let ub = self.size + 1;
-        for x in lb..=ub {
-            for y in lb..=ub {
-                for z in lb..=ub {
-                    for w in lb..=ub {
+        let mut next = Space4D::empty(self.size + 1);
+        let s = next.size;
+        for x in -s..=s {
+            for y in -s..=s {
+                for z in -s..=s {
+                    for w in -s..=s {
                         let p: Point4D = (x, y, z, w);
 
                         // look at neighbors
@@ -64,8 +107,7 @@ impl Space4D {
                                         if xd == 0 && yd == 0 && zd == 0 && wd == 0 {
                                             continue;
                                         }
-                                        let np: Point4D = (p.0 + xd, p.1 + yd, p.2 + zd, p.3 + wd);
-                                        if *self.inner.get(&np).unwrap_or(&false) {
+                                        if self.get((p.0 + xd, p.1 + yd, p.2 + zd, p.3 + wd)) {
                                             active_nbors += 1;
                                         }
                                     }
@@ -74,33 +116,27 @@ impl Space4D {
                         }
 
                         //set point
-                        let mut increase_size = false;
-                        if *self.inner.get(&p).unwrap_or(&false) {
-                            let new_state = active_nbors == 2 || active_nbors == 3;
-                            new_inner.insert(p, new_state);
+                        let new_state = if self.get(p) {
+                            active_nbors == 2 || active_nbors == 3
                         } else {
-                            let new_state = active_nbors == 3;
-                            new_inner.insert(p, new_state);
-                            if new_state
-                                && (x.abs() > self.size
-                                    || y.abs() > self.size
-                                    || z.abs() > self.size)
-                                || w.abs() > self.size
-                            {
-                                increase_size = true;
-                            }
-                        }
-                        self.size += if increase_size { 1 } else { 0 };
+                            active_nbors == 3
+                        };
+                        next.layers[(w + s) as usize][(z + s) as usize]
+                            [((x + s) as usize, (y + s) as usize)] = new_state;
                     }
                 }
             }
         }
-        self.inner = new_inner;
+        *self = next;
         Ok(())
     }
 
     pub fn active_count(self: &Self) -> usize {
-        self.inner.values().filter(|&v| *v).count()
+        self.layers
+            .iter()
+            .flatten()
+            .map(|layer| layer.iter().filter(|&&v| v).count())
+            .sum()
     }
 }
 
@@ -111,22 +147,11 @@ impl FromStr for Space4D {
     type Err = Space4DParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // assumes size is 1 or 3x3
-        let mut inner: HashMap<Point4D, bool> = HashMap::new();
-        let mut size: isize = 1;
-        for (li, line) in s.lines().enumerate() {
-            size = (line.len() as isize) / 2;
-            for (ci, c) in line.chars().enumerate() {
-                inner.insert((li as isize - size, ci as isize - size, 0, 0), c == '#');
-            }
-        }
-        Ok(Self { size, inner })
-    }
-}
-
-impl std::fmt::Debug for Space4D {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.inner)
+        let start = parse_slice(s).map_err(|_| Space4DParseError)?;
+        let mut space = Space4D::empty(start_size(&start));
+        let mid = space.size as usize;
+        space.layers[mid][mid] = centred(&start, space.size);
+        Ok(space)
     }
 }
 
@@ -135,21 +160,8 @@ impl std::fmt::Display for Space4D {
         for z in -self.size..=self.size {
             for w in -self.size..=self.size {
                 writeln!(f, "z={}, w={}", z, w)?;
-                for x in -self.size..=self.size {
-                    for y in -self.size..=self.size {
-                        write!(
-                            f,
-                            "{}",
-                            self.inner.get(&(x, y, z, w)).map_or('.', |&b| if b {
-                                '#'
-                            } else {
-                                '.'
-                            })
-                        )?;
-                    }
-                    writeln!(f)?;
-                }
-                writeln!(f)?;
+                let layer = &self.layers[(w + self.size) as usize][(z + self.size) as usize];
+                writeln!(f, "{}", cube_chars(layer))?;
             }
         }
         Ok(())
@@ -160,16 +172,32 @@ impl std::fmt::Display for Space4D {
 
 type Point = (isize, isize, isize);
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Debug)]
 struct Space {
     size: isize,
-    inner: HashMap<Point, bool>,
+    layers: Vec<Grid<bool>>, // [z]
 }
 
 #[derive(Debug)]
 struct SpaceStepError;
 
 impl Space {
+    fn empty(size: isize) -> Self {
+        let side = (2 * size + 1) as usize;
+        Space {
+            size,
+            layers: vec![Grid::new(side, side, false); side],
+        }
+    }
+
+    fn get(&self, (x, y, z): Point) -> bool {
+        let i = |v: isize| axis_index(v, self.size);
+        match (i(x), i(y), i(z)) {
+            (Some(x), Some(y), Some(z)) => self.layers[z][(x, y)],
+            _ => false,
+        }
+    }
+
     pub fn step_times(self: &mut Self, count: usize) -> Result<(), SpaceStepError> {
         for _ in 0..count {
             self.step()?;
@@ -177,13 +205,13 @@ impl Space {
         Ok(())
     }
 
+    // the active region can only grow by one cube in each direction per step
     pub fn step(self: &mut Self) -> Result<(), SpaceStepError> {
-        let mut new_inner: HashMap<Point, bool> = HashMap::new();
-        let lb = -self.size - 1;
-        let ub = self.size + 1;
-        for x in lb..=ub {
-            for y in lb..=ub {
-                for z in lb..=ub {
+        let mut next = Space::empty(self.size + 1);
+        let s = next.size;
+        for x in -s..=s {
+            for y in -s..=s {
+                for z in -s..=s {
                     let p: Point = (x, y, z);
 
                     // look at neighbors
@@ -194,8 +222,7 @@ impl Space {
                                 if xd == 0 && yd == 0 && zd == 0 {
                                     continue;
                                 }
-                                let np: Point = (p.0 + xd, p.1 + yd, p.2 + zd);
-                                if *self.inner.get(&np).unwrap_or(&false) {
+                                if self.get((p.0 + xd, p.1 + yd, p.2 + zd)) {
                                     active_nbors += 1;
                                 }
                             }
@@ -203,29 +230,24 @@ impl Space {
                     }
 
                     //set point
-                    let mut increase_size = false;
-                    if *self.inner.get(&p).unwrap_or(&false) {
-                        let new_state = active_nbors == 2 || active_nbors == 3;
-                        new_inner.insert(p, new_state);
+                    let new_state = if self.get(p) {
+                        active_nbors == 2 || active_nbors == 3
                     } else {
-                        let new_state = active_nbors == 3;
-                        new_inner.insert(p, new_state);
-                        if new_state
-                            && (x.abs() > self.size || y.abs() > self.size || z.abs() > self.size)
-                        {
-                            increase_size = true;
-                        }
-                    }
-                    self.size += if increase_size { 1 } else { 0 };
+                        active_nbors == 3
+                    };
+                    next.layers[(z + s) as usize][((x + s) as usize, (y + s) as usize)] = new_state;
                 }
             }
         }
-        self.inner = new_inner;
+        *self = next;
         Ok(())
     }
 
     pub fn active_count(self: &Self) -> usize {
-        self.inner.values().filter(|&v| *v).count()
+        self.layers
+            .iter()
+            .map(|layer| layer.iter().filter(|&&v| v).count())
+            .sum()
     }
 }
 
@@ -236,22 +258,11 @@ impl FromStr for Space {
     type Err = SpaceParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // assumes size is 1 or 3x3
-        let mut inner: HashMap<Point, bool> = HashMap::new();
-        let mut size: isize = 1;
-        for (li, line) in s.lines().enumerate() {
-            size = (line.len() as isize) / 2;
-            for (ci, c) in line.chars().enumerate() {
-                inner.insert((li as isize - size, ci as isize - size, 0), c == '#');
-            }
-        }
-        Ok(Self { size, inner })
-    }
-}
-
-impl std::fmt::Debug for Space {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.inner)
+        let start = parse_slice(s).map_err(|_| SpaceParseError)?;
+        let mut space = Space::empty(start_size(&start));
+        let mid = space.size as usize;
+        space.layers[mid] = centred(&start, space.size);
+        Ok(space)
     }
 }
 
@@ -259,24 +270,113 @@ impl std::fmt::Display for Space {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for z in -self.size..=self.size {
             writeln!(f, "z={}", z)?;
-            for x in -self.size..=self.size {
-                for y in -self.size..=self.size {
-                    write!(
-                        f,
-                        "{}",
-                        self.inner
-                            .get(&(x, y, z))
-                            .map_or('.', |&b| if b { '#' } else { '.' })
-                    )?;
-                }
-                writeln!(f)?;
-            }
-            writeln!(f)?;
+            writeln!(f, "{}", cube_chars(&self.layers[(z + self.size) as usize]))?;
         }
         Ok(())
     }
 }
 
+// position of coordinate `v` along an axis running -size..=size
+fn axis_index(v: isize, size: isize) -> Option<usize> {
+    if v.abs() <= size {
+        Some((v + size) as usize)
+    } else {
+        None
+    }
+}
+
+// The space is allocated whole in every dimension, so a slice much bigger than the puzzle's
+// 8x8 (another day's map, say) would ask for more memory than there is.
+const MAX_SLICE: usize = 64;
+
+fn parse_slice(s: &str) -> crate::parse::Result<Grid<bool>> {
+    let slice = Grid::from_chars(s, |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })?;
+    if slice.height().max(slice.width()) > MAX_SLICE {
+        return Err(crate::parse::ParseError {
+            line: 1,
+            column: 1,
+            message: format!(
+                "a {}x{} slice is too big, {} at most each way",
+                slice.height(),
+                slice.width(),
+                MAX_SLICE
+            ),
+        });
+    }
+    Ok(slice)
+}
+
+// the starting slice's cell (r, c) sits at (r - size, c - size)
+fn start_size(start: &Grid<bool>) -> isize {
+    (start.height().max(start.width()) / 2) as isize
+}
+
+fn centred(start: &Grid<bool>, size: isize) -> Grid<bool> {
+    let side = (2 * size + 1) as usize;
+    let mut layer = Grid::new(side, side, false);
+    for p in start.positions() {
+        layer[p] = start[p];
+    }
+    layer
+}
+
+fn cube_chars(layer: &Grid<bool>) -> Grid<char> {
+    layer.map(|_, &active| if active { '#' } else { '.' })
+}
+
+// one z-slice of a space, drawn over a fixed extent so every step renders at the same size
+struct SpaceSlice<'a> {
+    space: &'a Space,
+    z: isize,
+    extent: isize,
+}
+
+impl GridRender for SpaceSlice<'_> {
+    fn dimensions(&self) -> (usize, usize) {
+        let side = (2 * self.extent + 1) as usize;
+        (side, side)
+    }
+
+    fn cell_color(&self, row: usize, col: usize) -> Rgb {
+        let x = row as isize - self.extent;
+        let y = col as isize - self.extent;
+        if self.space.get((x, y, self.z)) {
+            render::PALETTE[1]
+        } else {
+            render::WHITE
+        }
+    }
+}
+
+// animates every z-slice over the six boot steps
+pub fn export(input: &str, dir: &Path, format: ImageFormat, scale: usize) -> std::io::Result<()> {
+    let steps = 6;
+    let mut spaces = vec![input.parse::<Space>().unwrap()];
+    for _ in 0..steps {
+        let mut next = spaces.last().unwrap().clone();
+        next.step().unwrap();
+        spaces.push(next);
+    }
+
+    let extent = spaces[0].size + steps as isize;
+    for z in -(steps as isize)..=(steps as isize) {
+        let slices = spaces
+            .iter()
+            .map(|space| SpaceSlice { space, z, extent })
+            .collect::<Vec<SpaceSlice>>();
+        let frames = slices
+            .iter()
+            .map(|s| s as &dyn GridRender)
+            .collect::<Vec<_>>();
+        render::write_frames(&frames, dir, &format!("day17_z{}", z), format, scale)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 #[warn(unused_must_use)]
 mod test {
@@ -302,4 +402,26 @@ mod test {
         assert_eq!(space.active_count(), 848);
         println!("{}", space);
     }
+
+    #[test]
+    fn test_slice() {
+        let space = INPUT1.parse::<Space>().unwrap();
+        let slice = SpaceSlice {
+            space: &space,
+            z: 0,
+            extent: 2,
+        };
+        assert_eq!(slice.dimensions(), (5, 5));
+        assert_eq!(slice.cell_color(1, 2), render::PALETTE[1]); // (-1, 0) is active
+        assert_eq!(slice.cell_color(1, 1), render::WHITE);
+        assert_eq!(slice.cell_color(0, 0), render::WHITE);
+    }
+
+    #[test]
+    fn test_too_big() {
+        let row = ".".repeat(MAX_SLICE + 1) + "\n";
+        let err = parse_slice(&row).unwrap_err();
+        assert_eq!(err.message, "a 1x65 slice is too big, 64 at most each way");
+        assert!(row.parse::<Space4D>().is_err());
+    }
 }
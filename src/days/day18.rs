@@ -1,8 +1,8 @@
+use crate::registry::{self, Outcome};
 use std::error::Error;
 use std::str::FromStr;
-pub fn day18() {
+pub fn day18(input: &str) {
     let mut sum = 0;
-    let input = include_str!("18.input");
     for line in input.lines() {
         let res = eval_str(line).unwrap();
         println!("{:>12} = {}", res, line);
@@ -19,6 +19,27 @@ pub fn day18() {
     println!("Using advanced math, the sum of all results is {}", sum);
 }
 
+pub fn solve(input: &str) -> Outcome {
+    registry::run(
+        input,
+        |s| s.lines().collect::<Vec<&str>>(),
+        part1,
+        Some(part2),
+    )
+}
+
+fn part1(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .map(|l| eval_str(l).unwrap())
+        .sum::<isize>()
+        .to_string()
+}
+
+fn part2(lines: &[&str]) -> String {
+    lines.iter().map(|l| eval_adv(l)).sum::<isize>().to_string()
+}
+
 #[derive(Debug, PartialEq)]
 struct EvalError;
 #[derive(Debug)]
@@ -1,14 +1,26 @@
+use crate::parse;
+use crate::profile;
+use crate::registry::{self, Outcome};
 use std::collections::HashMap;
 use std::str::FromStr;
 
-pub fn day19() {
-    let (rules, inputs) = parse_input(include_str!("19.input"));
+pub fn day19(input: &str) {
+    let (rules, inputs) = parse_input(input);
     println!(
         "{} of the inputs are valid",
         count_valid_inputs(&rules, inputs)
     );
 }
 
+// part 2 (looping rules) is not solved yet
+pub fn solve(input: &str) -> Outcome {
+    registry::run(input, parse_input, part1, None)
+}
+
+fn part1(notes: &(Rules, Vec<&str>)) -> String {
+    count_valid_inputs(&notes.0, notes.1.clone()).to_string()
+}
+
 type Rules = HashMap<usize, Rule>;
 
 #[derive(Debug)]
@@ -24,31 +36,23 @@ impl FromStr for Rule {
     type Err = RuleParseError;
 
     fn from_str(s: &'_ str) -> Result<Self, Self::Err> {
-        if let Some(pi) = s.find("|") {
-            let lh = s[0..pi]
-                .trim()
-                .split(" ")
-                .map(|r| r.parse::<usize>().expect("Unable to parse LHS of |"))
-                .collect::<Vec<usize>>();
-            let rh = s[pi + 2..]
-                .trim()
-                .split(" ")
-                .map(|r| r.parse::<usize>().expect("Unable to parse RHS of |"))
-                .collect::<Vec<usize>>();
-            return Ok(Rule::Disj(lh, rh));
-        } else if s.starts_with("\"") {
-            return Ok(Rule::Char(s.chars().nth(1).unwrap()));
+        let rule_nums = |s: &str| {
+            s.split_whitespace()
+                .map(|r| r.parse::<usize>().map_err(|_| RuleParseError))
+                .collect::<Result<Vec<usize>, RuleParseError>>()
+        };
+        if let Some((lh, rh)) = s.split_once('|') {
+            Ok(Rule::Disj(rule_nums(lh)?, rule_nums(rh)?))
+        } else if s.starts_with('"') {
+            s.chars().nth(1).map(Rule::Char).ok_or(RuleParseError)
         } else {
-            return Ok(Rule::Conj(
-                s.split(" ")
-                    .map(|v| v.parse::<usize>().unwrap())
-                    .collect::<Vec<usize>>(),
-            ));
+            Ok(Rule::Conj(rule_nums(s)?))
         }
     }
 }
 
 fn count_valid_inputs(rules: &Rules, inputs: Vec<&str>) -> usize {
+    let _span = profile::span("count_valid_inputs");
     let mut valid_count = 0;
     let mut total_count = 0;
     for input in inputs {
@@ -105,21 +109,30 @@ fn evaluate_rule_prefix(rule_num: usize, rules: &Rules, s: &str, i: &mut usize)
 }
 
 fn parse_input(s: &str) -> (Rules, Vec<&str>) {
-    let mut rules: HashMap<usize, Rule> = HashMap::new();
+    try_parse_input(s).unwrap_or_else(|e| panic!("day 19 input: {}", e))
+}
 
-    let mut lines = s.lines();
-    loop {
-        let line = lines.next().unwrap();
-        if line.is_empty() {
-            break;
+// rules ("0: 4 1 5"), a blank line, then the messages to check
+fn try_parse_input(s: &str) -> parse::Result<(Rules, Vec<&str>)> {
+    // the messages can be left off, as in some of the examples
+    let sections = {
+        let _span = profile::span("sections");
+        match parse::records(s).len() {
+            1 => parse::sections(s, 1)?,
+            _ => parse::sections(s, 2)?,
         }
-        let colon = line.find(":").unwrap();
-        let rule_num = line[0..colon].parse::<usize>().unwrap();
-        let rule = line[colon + 2..].parse::<Rule>().unwrap();
-        rules.insert(rule_num, rule);
+    };
+    let _span = profile::span("rules");
+    let mut rules: HashMap<usize, Rule> = HashMap::new();
+    for line in &sections[0] {
+        let (rule_num, rule) = line.key_value(":")?;
+        rules.insert(line.parse_at(rule_num)?, line.parse_at(rule)?);
     }
 
-    (rules, lines.collect::<Vec<&str>>())
+    let messages = sections
+        .get(1)
+        .map_or(Vec::new(), |m| m.iter().map(|l| l.text).collect());
+    Ok((rules, messages))
 }
 
 #[cfg(test)]
@@ -210,6 +223,12 @@ aaaabbb"#;
         assert!(!(evaluate_rule_prefix(0, &rules, s, &mut i) && dbg!(i) == s.len()));
     }
 
+    #[test]
+    fn test_parse_error() {
+        let err = try_parse_input("0: 1 2\n1: \"a\"\n2: 1 x\n\naa").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 4));
+    }
+
     #[test]
     fn test_batch_1() {
         let (rules, inputs) = parse_input(INPUT2);
@@ -0,0 +1,286 @@
+// Guesses which day a stray input file belongs to. Each registered day's parser is tried on it
+// first, and a day whose parser gives up scores nothing. Some parsers take nearly anything, so
+// the days left are ranked by a pattern their lines follow ("mem[8] = 11", "FBFBBFFRLR", ...):
+// the share of non-blank lines that match, scaled by a whole-file check where line shapes
+// alone can't tell days apart (days 1, 9 and 10 are all lists of numbers).
+use crate::registry;
+use regex::Regex;
+use std::collections::HashSet;
+use std::panic;
+
+struct Signature {
+    day: usize,
+    line: Regex,
+    fits: fn(&[&str]) -> f64, // 0..=1, how plausible the whole file is for this day
+}
+
+fn anything(_: &[&str]) -> f64 {
+    1.0
+}
+
+lazy_static! {
+    static ref SIGNATURES: Vec<Signature> = vec![
+        signature(1, r"^\d+$", pair_sums_to_2020),
+        signature(2, r"^\d+-\d+ [a-z]: [a-z]+$", anything),
+        signature(3, r"^[.#]+$", tall_grid),
+        signature(4, r"^([a-z]{3}:\S+\s*)+$", anything),
+        signature(5, r"^[FB]{7}[LR]{3}$", anything),
+        signature(6, r"^[a-z]+$", not_just_ab),
+        signature(7, r"^[a-z]+ [a-z]+ bags contain ", anything),
+        signature(8, r"^(nop|acc|jmp) [+-]\d+$", anything),
+        signature(9, r"^\d+$", xmas_sums),
+        signature(10, r"^\d+$", adapter_chain),
+        signature(11, r"^[.L#]+$", seat_map),
+        signature(12, r"^[NSEWLRF]\d+$", anything),
+        signature(13, r"^(\d+|((\d+|x),)+(\d+|x))$", bus_notes),
+        signature(14, r"^(mask = [01X]{36}|mem\[\d+\] = \d+)$", anything),
+        signature(15, r"^\d+(,\d+)+$", one_line),
+        signature(
+            16,
+            r"^([a-z ]+: \d+-\d+ or \d+-\d+|your ticket:|nearby tickets:|\d+(,\d+)*)$",
+            ticket_notes
+        ),
+        signature(17, r"^[.#]+$", small_square_grid),
+        signature(18, r"^[\d+*() ]*[+*][\d+*() ]*$", anything),
+        signature(19, r#"^(\d+: ("[a-z]"|[\d |]+)|[ab]+)$"#, anything),
+    ];
+}
+
+fn signature(day: usize, line: &str, fits: fn(&[&str]) -> f64) -> Signature {
+    Signature {
+        day,
+        line: Regex::new(line).unwrap(),
+        fits,
+    }
+}
+
+fn numbers(lines: &[&str]) -> Vec<u64> {
+    lines.iter().filter_map(|l| l.parse().ok()).collect()
+}
+
+// expense reports are all under 2020, and two of them add up to it
+fn pair_sums_to_2020(lines: &[&str]) -> f64 {
+    let nums = numbers(lines);
+    let seen = nums.iter().collect::<HashSet<&u64>>();
+    let pair = nums
+        .iter()
+        .any(|&n| n <= 2020 && seen.contains(&(2020 - n)));
+    match (pair, nums.iter().all(|&n| n <= 2020)) {
+        (true, true) => 1.0,
+        (true, false) | (false, true) => 0.3,
+        (false, false) => 0.1,
+    }
+}
+
+// share of the numbers after the first 25 that are a sum of two of the 25 before them
+fn xmas_sums(lines: &[&str]) -> f64 {
+    let nums = numbers(lines);
+    if nums.len() <= 25 {
+        return 0.1;
+    }
+    let valid = (25..nums.len())
+        .filter(|&i| {
+            let window = &nums[i - 25..i];
+            window.iter().enumerate().any(|(a, x)| {
+                window[a + 1..]
+                    .iter()
+                    .any(|y| x.checked_add(*y) == Some(nums[i]))
+            })
+        })
+        .count();
+    valid as f64 / (nums.len() - 25) as f64
+}
+
+fn adapter_chain(lines: &[&str]) -> f64 {
+    let mut nums = numbers(lines);
+    nums.push(0);
+    nums.sort_unstable();
+    if nums.windows(2).all(|w| (1..=3).contains(&(w[1] - w[0]))) {
+        1.0
+    } else {
+        0.1
+    }
+}
+
+fn grid_size(lines: &[&str]) -> Option<(usize, usize)> {
+    let width = lines.first()?.len();
+    if lines.iter().all(|l| l.len() == width) {
+        Some((lines.len(), width))
+    } else {
+        None
+    }
+}
+
+fn tall_grid(lines: &[&str]) -> f64 {
+    match grid_size(lines) {
+        Some((height, width)) if height > width => 1.0,
+        Some(_) => 0.5,
+        None => 0.2,
+    }
+}
+
+fn small_square_grid(lines: &[&str]) -> f64 {
+    match grid_size(lines) {
+        Some((height, width)) if height == width && height <= 16 => 1.0,
+        Some(_) => 0.3,
+        None => 0.1,
+    }
+}
+
+fn seat_map(lines: &[&str]) -> f64 {
+    match (grid_size(lines), lines.iter().any(|l| l.contains('L'))) {
+        (Some(_), true) => 1.0,
+        (None, true) => 0.5,
+        (_, false) => 0.1,
+    }
+}
+
+// a timestamp, then the bus list
+fn bus_notes(lines: &[&str]) -> f64 {
+    match lines {
+        [time, buses] if time.parse::<u64>().is_ok() && buses.contains(',') => 1.0,
+        _ => 0.1,
+    }
+}
+
+fn ticket_notes(lines: &[&str]) -> f64 {
+    if lines.contains(&"your ticket:") && lines.contains(&"nearby tickets:") {
+        1.0
+    } else {
+        0.1
+    }
+}
+
+// customs answers use the whole alphabet; only a's and b's is more like day 19's messages
+fn not_just_ab(lines: &[&str]) -> f64 {
+    let mut words = lines
+        .iter()
+        .filter(|l| l.chars().all(|c| c.is_ascii_lowercase()));
+    if words.any(|l| l.chars().any(|c| c != 'a' && c != 'b')) {
+        1.0
+    } else {
+        0.5
+    }
+}
+
+fn one_line(lines: &[&str]) -> f64 {
+    if lines.len() == 1 {
+        1.0
+    } else {
+        0.1
+    }
+}
+
+// (day, score in 0..=1) for every registered day with a signature, best first
+pub fn candidates(input: &str) -> Vec<(usize, f64)> {
+    let lines = input
+        .lines()
+        .map(|l| l.trim_end_matches('\r'))
+        .filter(|l| !l.trim().is_empty())
+        .collect::<Vec<&str>>();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+    let mut scores = registry::days()
+        .iter()
+        .filter_map(|day| {
+            let s = SIGNATURES.iter().find(|s| s.day == day.day)?;
+            let matching = lines.iter().filter(|l| s.line.is_match(l)).count();
+            let share = matching as f64 / lines.len() as f64;
+            let score = if share > 0.0 && registry::parses(day, input) {
+                share * (s.fits)(&lines)
+            } else {
+                0.0
+            };
+            Some((s.day, score))
+        })
+        .collect::<Vec<(usize, f64)>>();
+    scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+    scores
+}
+
+fn confidence(best: f64, runner_up: f64) -> &'static str {
+    if best >= 0.9 && best - runner_up >= 0.3 {
+        "high"
+    } else if best >= 0.6 && best - runner_up >= 0.1 {
+        "medium"
+    } else {
+        "low"
+    }
+}
+
+pub fn detect(file: &str) -> std::io::Result<()> {
+    let input = std::fs::read_to_string(file)?;
+    // the parsers that give up on it panic, which is only noise here
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let candidates = candidates(&input);
+    panic::set_hook(hook);
+    let shortlist = candidates
+        .iter()
+        .filter(|(_, score)| *score > 0.0)
+        .take(3)
+        .collect::<Vec<_>>();
+    match shortlist.first() {
+        None => println!("{} doesn't look like any day's input", file),
+        Some(&&(day, best)) => {
+            let runner_up = shortlist.get(1).map_or(0.0, |c| c.1);
+            println!(
+                "{} looks like day {} ({} confidence)",
+                file,
+                day,
+                confidence(best, runner_up)
+            );
+            for (day, score) in &shortlist {
+                println!("  day {:>2}  {:>5.1}%", day, score * 100.0);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn best(input: &str) -> usize {
+        candidates(input)[0].0
+    }
+
+    #[test]
+    fn test_real_inputs() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("inputs");
+        for day in registry::days() {
+            let input = std::fs::read_to_string(dir.join(format!("{}.input", day.day))).unwrap();
+            let candidates = candidates(&input);
+            assert_eq!(candidates[0].0, day.day, "{:?}", &candidates[..3]);
+            assert!(candidates[0].1 - candidates[1].1 >= 0.1, "day {}", day.day);
+        }
+    }
+
+    #[test]
+    fn test_examples() {
+        assert_eq!(best("FBFBBFFRLR\nBFFFBBFRRR\n"), 5);
+        assert_eq!(best("nop +0\nacc +1\njmp +4\n"), 8);
+        assert_eq!(
+            best("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\nmem[8] = 11\n"),
+            14
+        );
+        assert_eq!(best(".#.\n..#\n###\n"), 17);
+        assert!(candidates("").is_empty());
+        assert!(candidates("hello, world\n").iter().all(|c| c.1 == 0.0));
+        // lines of digits, but too big for any of the number lists' parsers
+        let huge = "99999999999999999999999\n1\n";
+        assert!(candidates(huge).iter().all(|c| c.1 == 0.0));
+    }
+
+    #[test]
+    fn test_large_numbers() {
+        // sums of two u64s near the top would overflow
+        let input = (0..30)
+            .map(|i| (u64::MAX - i).to_string())
+            .collect::<Vec<String>>()
+            .join("\n");
+        assert!(xmas_sums(&input.lines().collect::<Vec<&str>>()) < 0.5);
+    }
+}
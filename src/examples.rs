@@ -0,0 +1,196 @@
+// Puzzle examples kept as files, so adding one doesn't mean writing a test. Each lives at
+// examples/dayNN/<name>.txt: expected answers in a header, a `---` line, then the input.
+//
+//     part1: 7
+//     part2: 336
+//     ---
+//     ..##.......
+//
+// Either answer can be left out (e.g. when part 2 needs a different parameter than the real
+// input). `test_examples` runs every file through the registry.
+use crate::parse;
+use crate::registry;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, PartialEq)]
+pub struct Example {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+    pub input: String,
+}
+
+pub fn parse_example(s: &str) -> parse::Result<Example> {
+    let mut part1 = None;
+    let mut part2 = None;
+    let mut lines = parse::lines(s);
+    let mut last = 0;
+    loop {
+        let line = match lines.next() {
+            Some(line) => line,
+            None => {
+                return Err(parse::ParseError {
+                    line: last,
+                    column: 1,
+                    message: String::from(
+                        "expected a `---` line between the answers and the input",
+                    ),
+                })
+            }
+        };
+        last = line.number;
+        if line.text.trim() == "---" {
+            break;
+        }
+        if line.text.trim().is_empty() {
+            continue;
+        }
+        let (key, value) = line.key_value(":")?;
+        match key {
+            "part1" => part1 = Some(String::from(value)),
+            "part2" => part2 = Some(String::from(value)),
+            _ => return Err(line.error(key, format!("unknown header {:?}", key))),
+        }
+    }
+    let input = lines.map(|l| l.text).collect::<Vec<&str>>().join("\n");
+    Ok(Example {
+        part1,
+        part2,
+        input,
+    })
+}
+
+// (day, file) for every examples/dayNN/*.txt, in order
+pub fn discover(dir: &Path) -> io::Result<Vec<(usize, PathBuf)>> {
+    let mut found = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let day = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_prefix("day"))
+            .and_then(|n| n.parse::<usize>().ok());
+        if let (Some(day), true) = (day, path.is_dir()) {
+            for file in fs::read_dir(&path)? {
+                let file = file?.path();
+                if file.extension().is_some_and(|e| e == "txt") {
+                    found.push((day, file));
+                }
+            }
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+// None if the example passes, otherwise what went wrong
+fn check(day: usize, path: &Path) -> Option<String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => return Some(format!("can't read it: {}", e)),
+    };
+    let example = match parse_example(&text) {
+        Ok(example) => example,
+        Err(e) => return Some(format!("bad example file: {}", e)),
+    };
+    let solve = match registry::days().into_iter().find(|d| d.day == day) {
+        Some(d) => d.solve,
+        None => return Some(format!("no solution registered for day {}", day)),
+    };
+    // one example the solver chokes on shouldn't stop the rest from being checked
+    let outcome = match panic::catch_unwind(|| solve(&example.input)) {
+        Ok(outcome) => outcome,
+        Err(payload) => {
+            let message = match (
+                payload.downcast_ref::<String>(),
+                payload.downcast_ref::<&str>(),
+            ) {
+                (Some(message), _) => message.clone(),
+                (None, Some(message)) => String::from(*message),
+                (None, None) => String::from("no message"),
+            };
+            return Some(format!("the solver panicked: {}", message));
+        }
+    };
+    let mismatches = [
+        (1, &example.part1, &outcome.part1),
+        (2, &example.part2, &outcome.part2),
+    ]
+    .iter()
+    .filter_map(|(part, expected, actual)| match (expected, actual) {
+        (Some(e), Some(a)) if e == a => None,
+        (Some(e), a) => Some(format!("part {} expected {}, got {:?}", part, e, a)),
+        (None, _) => None,
+    })
+    .collect::<Vec<String>>();
+    if mismatches.is_empty() {
+        None
+    } else {
+        Some(mismatches.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_example() {
+        let example = parse_example("part2: 336\n\n---\n..#\n#..\n").unwrap();
+        assert_eq!(
+            example,
+            Example {
+                part1: None,
+                part2: Some(String::from("336")),
+                input: String::from("..#\n#.."),
+            }
+        );
+        assert_eq!(parse_example("part1: 7\n..#").unwrap_err().line, 2);
+        assert_eq!(parse_example("answer: 7\n---\n").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn test_check_panics() {
+        let path =
+            std::env::temp_dir().join(format!("aoc-example-test-{}.txt", std::process::id()));
+        fs::write(&path, "part1: 514579\n---\n1721\nnot a number\n").unwrap();
+        let problem = check(1, &path);
+        fs::remove_file(&path).unwrap();
+        assert!(
+            problem
+                .as_deref()
+                .unwrap_or("")
+                .starts_with("the solver panicked: "),
+            "{:?}",
+            problem
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+        let examples = discover(&dir).unwrap();
+        assert!(!examples.is_empty(), "no examples in {}", dir.display());
+
+        let mut failures = 0;
+        for (day, path) in &examples {
+            let name = path.strip_prefix(&dir).unwrap_or(path).display();
+            match check(*day, path) {
+                None => println!("PASS {}", name),
+                Some(problem) => {
+                    println!("FAIL {}: {}", name, problem);
+                    failures += 1;
+                }
+            }
+        }
+        assert_eq!(
+            failures,
+            0,
+            "{} of {} examples failed",
+            failures,
+            examples.len()
+        );
+    }
+}
@@ -0,0 +1,252 @@
+// A rectangular grid in one flat, row-major Vec, for the days whose input is a character map.
+// Positions are (row, col); directions are (row step, col step).
+use crate::parse;
+use std::fmt;
+use std::iter;
+use std::ops::{Index, IndexMut};
+
+pub type Pos = (usize, usize);
+pub type Dir = (isize, isize);
+
+pub const ORTHOGONAL: [Dir; 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+pub const DIRECTIONS: [Dir; 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(height: usize, width: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    // One row per non-blank line, one cell per character. `cell` returns None for characters
+    // that don't belong in the map; those and ragged rows are reported with their position.
+    pub fn from_chars(input: &str, cell: impl Fn(char) -> Option<T>) -> parse::Result<Self> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for line in parse::lines(input).filter(|l| !l.text.trim().is_empty()) {
+            let text = line.text.trim_end();
+            let row_width = text.chars().count();
+            match width {
+                None => width = Some(row_width),
+                Some(w) if w != row_width => {
+                    return Err(
+                        line.error(text, format!("row is {} wide, expected {}", row_width, w))
+                    )
+                }
+                Some(_) => (),
+            }
+            for (i, c) in text.char_indices() {
+                match cell(c) {
+                    Some(value) => cells.push(value),
+                    None => return Err(line.error(&text[i..], format!("unexpected {:?}", c))),
+                }
+            }
+            height += 1;
+        }
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (row, col): Pos) -> Option<&T> {
+        if row < self.height && col < self.width {
+            Some(&self.cells[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, (row, col): Pos) -> Option<&mut T> {
+        if row < self.height && col < self.width {
+            Some(&mut self.cells[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    // indexing that wraps around both edges, like the day 3 map repeating to the right.
+    // Panics on an empty grid.
+    #[allow(dead_code)] // day 3 keeps its map in a `BitGrid` now
+    pub fn get_wrapped(&self, row: isize, col: isize) -> &T {
+        let row = row.rem_euclid(self.height as isize) as usize;
+        let col = col.rem_euclid(self.width as isize) as usize;
+        &self.cells[row * self.width + col]
+    }
+
+    // one step from `pos` in direction `dir`, if that's still on the grid
+    pub fn offset(&self, (row, col): Pos, (dr, dc): Dir) -> Option<Pos> {
+        let row = row.checked_add_signed(dr)?;
+        let col = col.checked_add_signed(dc)?;
+        if row < self.height && col < self.width {
+            Some((row, col))
+        } else {
+            None
+        }
+    }
+
+    // every position from `pos` (exclusive) to the edge of the grid in direction `dir`
+    pub fn ray(&self, pos: Pos, dir: Dir) -> impl Iterator<Item = Pos> + '_ {
+        iter::successors(self.offset(pos, dir), move |&p| self.offset(p, dir))
+    }
+
+    #[allow(dead_code)] // none of the days so far only look up/down/left/right
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        ORTHOGONAL.iter().filter_map(move |&d| self.offset(pos, d))
+    }
+
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRECTIONS.iter().filter_map(move |&d| self.offset(pos, d))
+    }
+
+    // the first cell in each of the eight directions that `see_through` doesn't skip over
+    pub fn line_of_sight<'a>(
+        &'a self,
+        pos: Pos,
+        see_through: impl Fn(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = Pos> + 'a {
+        DIRECTIONS
+            .iter()
+            .filter_map(move |&d| self.ray(pos, d).find(|&p| !see_through(&self[p])))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn map<U>(&self, f: impl Fn(Pos, &T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.positions().map(|p| f(p, &self[p])).collect(),
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", pos))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", pos))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn grid() -> Grid<char> {
+        Grid::from_chars("ab.\n.c#\r\n\n", Some).unwrap()
+    }
+
+    #[test]
+    fn test_from_chars() {
+        let g = grid();
+        assert_eq!((g.height(), g.width()), (2, 3));
+        assert_eq!(g[(1, 2)], '#');
+        assert_eq!(g.get((2, 0)), None);
+        assert_eq!(g.to_string(), "ab.\n.c#\n");
+
+        let err = Grid::from_chars("..\n...", Some).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        let err = Grid::from_chars("..#\n.x.", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+    }
+
+    #[test]
+    fn test_wrapped() {
+        let g = grid();
+        assert_eq!(*g.get_wrapped(0, 4), 'b');
+        assert_eq!(*g.get_wrapped(-1, -1), '#');
+        assert_eq!(*g.get_wrapped(5, 3), '.');
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let g = grid();
+        assert_eq!(
+            g.neighbours4((0, 0)).collect::<Vec<Pos>>(),
+            vec![(0, 1), (1, 0)]
+        );
+        assert_eq!(g.neighbours8((0, 1)).count(), 5);
+        assert_eq!(g.neighbours8((1, 1)).count(), 5);
+        assert_eq!(
+            g.ray((0, 0), (0, 1)).collect::<Vec<Pos>>(),
+            vec![(0, 1), (0, 2)]
+        );
+        // looking past the '.' cells from the top-left corner
+        let seen = g.line_of_sight((0, 0), |&c| c == '.').collect::<Vec<Pos>>();
+        assert_eq!(seen, vec![(0, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn test_map() {
+        let g = grid().map(|(row, col), &c| (row + col, c == '.'));
+        assert_eq!(g[(1, 2)], (3, false));
+        assert_eq!(g.iter().filter(|(_, dot)| *dot).count(), 2);
+    }
+}
@@ -0,0 +1,114 @@
+// Where puzzle inputs come from. With the `embed-inputs` feature (on by default) every input in
+// inputs/ is compiled into the binary, so it runs from anywhere; without it, inputs are read
+// from the input directory at runtime and editing one doesn't mean a rebuild. Either way
+// callers just ask `Inputs::get` for a day. Files that change how a day is solved, like day 4's
+// passport rules, can sit in the input directory too; `Inputs::config` finds them.
+use std::borrow::Cow;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// the repo's inputs/, used when no directory is given and nothing is embedded
+#[cfg(not(feature = "embed-inputs"))]
+const DEFAULT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/inputs");
+
+#[cfg(feature = "embed-inputs")]
+const EMBEDDED: [(usize, &str); 19] = [
+    (1, include_str!("../inputs/1.input")),
+    (2, include_str!("../inputs/2.input")),
+    (3, include_str!("../inputs/3.input")),
+    (4, include_str!("../inputs/4.input")),
+    (5, include_str!("../inputs/5.input")),
+    (6, include_str!("../inputs/6.input")),
+    (7, include_str!("../inputs/7.input")),
+    (8, include_str!("../inputs/8.input")),
+    (9, include_str!("../inputs/9.input")),
+    (10, include_str!("../inputs/10.input")),
+    (11, include_str!("../inputs/11.input")),
+    (12, include_str!("../inputs/12.input")),
+    (13, include_str!("../inputs/13.input")),
+    (14, include_str!("../inputs/14.input")),
+    (15, include_str!("../inputs/15.input")),
+    (16, include_str!("../inputs/16.input")),
+    (17, include_str!("../inputs/17.input")),
+    (18, include_str!("../inputs/18.input")),
+    (19, include_str!("../inputs/19.input")),
+];
+
+pub struct Inputs {
+    dir: Option<PathBuf>, // None: use the embedded inputs
+}
+
+impl Inputs {
+    // An explicit `dir` always wins. Otherwise it's the embedded inputs, or the repo's inputs/
+    // directory when they aren't compiled in.
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        #[cfg(not(feature = "embed-inputs"))]
+        let dir = dir.or_else(|| Some(PathBuf::from(DEFAULT_DIR)));
+        Inputs { dir }
+    }
+
+    pub fn get(&self, day: usize) -> io::Result<Cow<'static, str>> {
+        match &self.dir {
+            Some(dir) => {
+                let path = dir.join(format!("{}.input", day));
+                fs::read_to_string(&path)
+                    .map(Cow::Owned)
+                    .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+            }
+            None => embedded(day).map(Cow::Borrowed).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no embedded input for day {}", day),
+                )
+            }),
+        }
+    }
+
+    // the file `name` in the input directory, if there is a directory and it has one
+    pub fn config(&self, name: &str) -> Option<PathBuf> {
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(name))
+            .filter(|path| path.exists())
+    }
+}
+
+#[cfg(feature = "embed-inputs")]
+fn embedded(day: usize) -> Option<&'static str> {
+    EMBEDDED.iter().find(|(d, _)| *d == day).map(|(_, s)| *s)
+}
+
+#[cfg(not(feature = "embed-inputs"))]
+fn embedded(_day: usize) -> Option<&'static str> {
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dir() {
+        let inputs = Inputs::new(Some(
+            [env!("CARGO_MANIFEST_DIR"), "inputs"].iter().collect(),
+        ));
+        assert_eq!(inputs.get(15).unwrap(), "20,9,11,0,1,2\n");
+        let err = inputs.get(25).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("25.input"));
+        assert_eq!(
+            inputs.config("15.input"),
+            inputs.dir.map(|d| d.join("15.input"))
+        );
+        assert_eq!(Inputs::new(None).config("passports.schema"), None);
+    }
+
+    #[test]
+    fn test_default() {
+        // embedded or read from inputs/, the default provider has the same inputs
+        let inputs = Inputs::new(None);
+        assert_eq!(inputs.get(15).unwrap(), "20,9,11,0,1,2\n");
+        assert!(inputs.get(25).is_err());
+    }
+}
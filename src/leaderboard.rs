@@ -0,0 +1,440 @@
+// Offline stats for a private leaderboard, from the JSON that the leaderboard page exports:
+//
+//     {"event": "2020", "members": {"123": {"id": 123, "name": "...",
+//         "completion_day_level": {"1": {"1": {"get_star_ts": 1606799127}, "2": {...}}}}}}
+//
+// Older exports give the timestamps as strings. Puzzles unlock at midnight EST (05:00 UTC), so
+// solve times are measured from then.
+use serde_json::{json, Value};
+use std::fs;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Star {
+    pub day: usize,
+    pub part: usize,
+    pub ts: i64, // unix seconds
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub id: String,
+    pub name: String,
+    pub stars: Vec<Star>, // by day, then part
+}
+
+#[derive(Debug)]
+pub struct Leaderboard {
+    pub year: i64,
+    pub members: Vec<Member>,
+}
+
+impl Member {
+    fn star(&self, day: usize, part: usize) -> Option<&Star> {
+        self.stars.iter().find(|s| s.day == day && s.part == part)
+    }
+
+    // part 1 to part 2, for days with both stars
+    fn gap(&self, day: usize) -> Option<i64> {
+        Some(self.star(day, 2)?.ts - self.star(day, 1)?.ts)
+    }
+}
+
+fn timestamp(v: &Value) -> Option<i64> {
+    match v {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+pub fn parse(json: &str) -> Result<Leaderboard, String> {
+    let root: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let year = root
+        .get("event")
+        .and_then(timestamp)
+        .ok_or("missing \"event\" (the year)")?;
+    let members = root
+        .get("members")
+        .and_then(Value::as_object)
+        .ok_or("missing \"members\"")?;
+
+    let mut parsed = Vec::new();
+    for (id, m) in members {
+        let name = match m.get("name") {
+            Some(Value::String(name)) => name.clone(),
+            _ => format!("(anonymous user #{})", id), // what the site shows for unnamed members
+        };
+        let mut stars = Vec::new();
+        if let Some(days) = m.get("completion_day_level").and_then(Value::as_object) {
+            for (day, parts) in days {
+                let day = day
+                    .parse::<usize>()
+                    .map_err(|_| format!("member {}: bad day {:?}", id, day))?;
+                for (part, star) in parts.as_object().into_iter().flatten() {
+                    let part = part
+                        .parse::<usize>()
+                        .map_err(|_| format!("member {}: bad part {:?}", id, part))?;
+                    let ts = star.get("get_star_ts").and_then(timestamp).ok_or(format!(
+                        "member {}: day {} part {} has no time",
+                        id, day, part
+                    ))?;
+                    stars.push(Star { day, part, ts });
+                }
+            }
+        }
+        stars.sort_by_key(|s| (s.day, s.part));
+        parsed.push(Member {
+            id: id.clone(),
+            name,
+            stars,
+        });
+    }
+    parsed.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Leaderboard {
+        year,
+        members: parsed,
+    })
+}
+
+// days since 1970-01-01 for a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// the reverse: (year, month, day)
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + if month <= 2 { 1 } else { 0 }, month, day)
+}
+
+pub fn unlock_ts(year: i64, day: usize) -> i64 {
+    days_from_civil(year, 12, day as i64) * 86_400 + 5 * 3600
+}
+
+fn format_ts(ts: i64) -> String {
+    let (y, mo, d) = civil_from_days(ts.div_euclid(86_400));
+    let secs = ts.rem_euclid(86_400);
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}:{:02}",
+        y,
+        mo,
+        d,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+// h:mm:ss, or "Nd h:mm:ss" past a day
+fn format_span(secs: i64) -> String {
+    let hms = format!(
+        "{}:{:02}:{:02}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    );
+    match secs / 86_400 {
+        0 => hms,
+        days => format!("{}d {}", days, hms),
+    }
+}
+
+impl Leaderboard {
+    fn solve_time(&self, star: &Star) -> i64 {
+        star.ts - unlock_ts(self.year, star.day)
+    }
+
+    fn days(&self) -> Vec<usize> {
+        let mut days = self
+            .members
+            .iter()
+            .flat_map(|m| m.stars.iter().map(|s| s.day))
+            .collect::<Vec<usize>>();
+        days.sort_unstable();
+        days.dedup();
+        days
+    }
+
+    // Points by finishing order: with n members the first to finish gets n, the next n - 1, ...
+    // `key` gives what is compared for a member on a day, if they qualify.
+    fn order_points(&self, key: impl Fn(&Member, usize) -> Option<i64>) -> Vec<usize> {
+        let n = self.members.len();
+        let mut points = vec![0; n];
+        for day in self.days() {
+            let mut finishers = (0..n)
+                .filter_map(|i| key(&self.members[i], day).map(|k| (k, i)))
+                .collect::<Vec<(i64, usize)>>();
+            finishers.sort_unstable();
+            for (place, (_, i)) in finishers.into_iter().enumerate() {
+                points[i] += n - place;
+            }
+        }
+        points
+    }
+}
+
+// One way of ranking members. `score` is per member, higher ranks first (times are negated).
+pub struct Rule {
+    pub name: &'static str,
+    pub about: &'static str,
+    pub score: fn(&Leaderboard) -> Vec<i64>,
+}
+
+pub fn rules() -> Vec<Rule> {
+    vec![
+        Rule {
+            name: "local",
+            about: "the site's scoring: n points for the first to finish a part, n - 1 next...",
+            score: |lb| {
+                let part = |p| lb.order_points(|m, day| m.star(day, p).map(|s| s.ts));
+                part(1)
+                    .iter()
+                    .zip(part(2))
+                    .map(|(a, b)| (a + b) as i64)
+                    .collect()
+            },
+        },
+        Rule {
+            name: "stars",
+            about: "stars earned",
+            score: |lb| lb.members.iter().map(|m| m.stars.len() as i64).collect(),
+        },
+        Rule {
+            name: "time",
+            about: "most stars, then least total solve time (negated seconds)",
+            score: |lb| {
+                lb.members
+                    .iter()
+                    .map(|m| {
+                        let total = m.stars.iter().map(|s| lb.solve_time(s)).sum::<i64>();
+                        m.stars.len() as i64 * 1_000_000_000_000 - total
+                    })
+                    .collect()
+            },
+        },
+        Rule {
+            name: "delta",
+            about: "local-score points for the shortest gap between part 1 and part 2",
+            score: |lb| {
+                lb.order_points(|m, day| m.gap(day))
+                    .into_iter()
+                    .map(|p| p as i64)
+                    .collect()
+            },
+        },
+    ]
+}
+
+// 1-based rank of each member under `scores`, sharing ranks on ties
+fn ranks(scores: &[i64]) -> Vec<usize> {
+    scores
+        .iter()
+        .map(|s| 1 + scores.iter().filter(|other| *other > s).count())
+        .collect()
+}
+
+pub fn table(lb: &Leaderboard) -> String {
+    let rules = rules();
+    let scores = rules.iter().map(|r| (r.score)(lb)).collect::<Vec<_>>();
+    let ranks = scores.iter().map(|s| ranks(s)).collect::<Vec<_>>();
+    let width = lb
+        .members
+        .iter()
+        .map(|m| m.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(6);
+
+    let mut out = format!("Rankings for {} (rank by each rule)\n", lb.year);
+    out += &format!("{:<width$}", "member", width = width);
+    for r in &rules {
+        out += &format!(" {:>6}", r.name);
+    }
+    out += "\n";
+    let mut order = (0..lb.members.len()).collect::<Vec<usize>>();
+    order.sort_by_key(|&i| (ranks[0][i], i));
+    for &i in &order {
+        out += &format!("{:<width$}", lb.members[i].name, width = width);
+        for r in &ranks {
+            out += &format!(" {:>6}", r[i]);
+        }
+        out += "\n";
+    }
+    for r in &rules {
+        out += &format!("  {}: {}\n", r.name, r.about);
+    }
+
+    for m in &lb.members {
+        out += &format!("\n{} (id {}), {} stars\n", m.name, m.id, m.stars.len());
+        if m.stars.is_empty() {
+            continue;
+        }
+        out += &format!(
+            "{:>4}  {:<19} {:>11}  {:<19} {:>11}  {:>11}\n",
+            "day", "part 1 at (UTC)", "solve", "part 2 at (UTC)", "solve", "gap"
+        );
+        let mut days = m.stars.iter().map(|s| s.day).collect::<Vec<usize>>();
+        days.dedup();
+        for day in days {
+            let cells = |part| match m.star(day, part) {
+                Some(s) => (format_ts(s.ts), format_span(lb.solve_time(s))),
+                None => (String::from("-"), String::from("-")),
+            };
+            let ((at1, solve1), (at2, solve2)) = (cells(1), cells(2));
+            let gap = m.gap(day).map_or(String::from("-"), format_span);
+            out += &format!(
+                "{:>4}  {:<19} {:>11}  {:<19} {:>11}  {:>11}\n",
+                day, at1, solve1, at2, solve2, gap
+            );
+        }
+    }
+    out
+}
+
+pub fn to_json(lb: &Leaderboard) -> Value {
+    let rules = rules();
+    let scores = rules.iter().map(|r| (r.score)(lb)).collect::<Vec<_>>();
+    let ranks = scores.iter().map(|s| ranks(s)).collect::<Vec<_>>();
+    let members = lb
+        .members
+        .iter()
+        .enumerate()
+        .map(|(i, m)| {
+            let stars = m
+                .stars
+                .iter()
+                .map(|s| {
+                    json!({
+                        "day": s.day,
+                        "part": s.part,
+                        "timestamp": s.ts,
+                        "solve_seconds": lb.solve_time(s),
+                    })
+                })
+                .collect::<Vec<Value>>();
+            let gaps = m
+                .stars
+                .iter()
+                .filter_map(|s| Some((s.day.to_string(), json!(m.gap(s.day)?))))
+                .collect::<serde_json::Map<String, Value>>();
+            let rankings = rules
+                .iter()
+                .enumerate()
+                .map(|(r, rule)| {
+                    let entry = json!({"score": scores[r][i], "rank": ranks[r][i]});
+                    (String::from(rule.name), entry)
+                })
+                .collect::<serde_json::Map<String, Value>>();
+            json!({
+                "id": m.id,
+                "name": m.name,
+                "stars": stars,
+                "gap_seconds": gaps,
+                "rankings": rankings,
+            })
+        })
+        .collect::<Vec<Value>>();
+    json!({"year": lb.year, "members": members})
+}
+
+pub fn leaderboard(file: &str, as_json: bool) -> Result<(), String> {
+    let text = fs::read_to_string(file).map_err(|e| format!("can't read {}: {}", file, e))?;
+    let lb = parse(&text).map_err(|e| format!("{}: {}", file, e))?;
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&to_json(&lb)).unwrap());
+    } else {
+        print!("{}", table(&lb));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 2020-12-01 05:00 UTC is 1606798800
+    const EXPORT: &str = r#"{
+        "event": "2020",
+        "owner_id": "1",
+        "members": {
+            "1": {"id": "1", "name": "ada", "stars": 3, "local_score": 0,
+                  "completion_day_level": {
+                      "1": {"1": {"get_star_ts": "1606799100"}, "2": {"get_star_ts": "1606799400"}},
+                      "2": {"1": {"get_star_ts": 1606886000}}}},
+            "2": {"id": 2, "name": "bob", "stars": 2,
+                  "completion_day_level": {
+                      "1": {"1": {"get_star_ts": 1606799000}, "2": {"get_star_ts": 1606801000}}}},
+            "3": {"id": 3, "name": null, "completion_day_level": {}}
+        }
+    }"#;
+
+    #[test]
+    fn test_parse() {
+        let lb = parse(EXPORT).unwrap();
+        assert_eq!(lb.year, 2020);
+        let names = lb
+            .members
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, ["(anonymous user #3)", "ada", "bob"]);
+        assert_eq!(
+            lb.members[1].stars[2],
+            Star {
+                day: 2,
+                part: 1,
+                ts: 1606886000
+            }
+        );
+        assert!(parse("{\"event\": \"2020\"}").is_err());
+        assert!(parse("not json").is_err());
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(unlock_ts(2020, 1), 1606798800);
+        assert_eq!(format_ts(1606799100), "2020-12-01 05:05:00");
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+        assert_eq!(format_span(300), "0:05:00");
+        assert_eq!(format_span(86_400 + 61), "1d 0:01:01");
+    }
+
+    #[test]
+    fn test_rules() {
+        let lb = parse(EXPORT).unwrap();
+        let score = |name: &str| {
+            let rule = rules().into_iter().find(|r| r.name == name).unwrap();
+            (rule.score)(&lb)
+        };
+        // members are [anonymous, ada, bob]; three members, so 3 points for a first place
+        assert_eq!(score("local"), [0, 2 + 3 + 3, 3 + 2]);
+        assert_eq!(score("stars"), [0, 3, 2]);
+        assert_eq!(score("delta"), [0, 3, 2]);
+        assert_eq!(ranks(&score("stars")), [3, 1, 2]);
+        assert_eq!(ranks(&[5, 5, 1]), [1, 1, 3]);
+    }
+
+    #[test]
+    fn test_json() {
+        let lb = parse(EXPORT).unwrap();
+        let json = to_json(&lb);
+        let ada = &json["members"][1];
+        assert_eq!(ada["name"], "ada");
+        assert_eq!(ada["gap_seconds"]["1"], 300);
+        assert_eq!(ada["stars"][0]["solve_seconds"], 300);
+        assert_eq!(ada["rankings"]["local"]["rank"], 1);
+        assert!(table(&lb).contains("bob (id 2), 2 stars"));
+    }
+}
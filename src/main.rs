@@ -1,202 +1,2238 @@
-use clap::{App, Arg};
-use num_traits::pow;
+use cache::Cache;
+use inputs::Inputs;
+use std::io;
+use std::path::{Path, PathBuf};
 
 #[macro_use]
 extern crate lazy_static;
 
+#[cfg(feature = "bench-history")]
+mod bench_history;
+mod bitboard;
+mod cache;
+mod cli;
 mod days;
+mod detect;
+#[cfg(test)]
+mod examples;
+mod grid;
+mod inputs;
+mod leaderboard;
+mod numtheory;
+mod parse;
+mod profile;
+mod registry;
+mod render;
+mod report;
+mod scale;
 
 fn main() {
-    let matches = App::new("Advent of Code 2020")
-        .version("1.0.0")
-        .author("Mike Erickson <mike.erickson@gmail.com>")
-        .arg(
-            Arg::with_name("day")
-                .short("d")
-                .long("day")
-                .takes_value(true)
-                .help("day or exercise"),
-        )
-        .get_matches();
-
-    match matches.value_of("day").expect("day not specfied") {
-        "1" => day1(),
-        "2" => day2(),
-        "3" => day3(),
-        "4" => day4(),
-        "5" => day5(),
-        "6" => day6(),
-        "7" => day7(),
-        "8" => day8::day8(),
-        "9" => day9::day9(),
-        "10" => day10::day10(),
-        "11" => day11::day11(),
-        "12" => day12::day12(),
-        "13" => day13::day13(),
-        "14" => day14::day14(),
-        "15" => days::day15::day15(),
-        "16" => days::day16::day16(),
-        "17" => days::day17::day17(),
-        "18" => days::day18::day18(),
-        "19" => days::day19::day19(),
+    let matches = cli::cli().get_matches();
+    if let Some(m) = matches.subcommand_matches("completions") {
+        if let Err(e) = cli::completions(m.value_of("shell").unwrap(), &mut io::stdout()) {
+            eprintln!("{}", e);
+        }
+        return;
+    }
+    if matches.subcommand_matches("manpage").is_some() {
+        if let Err(e) = cli::manpage(&mut io::stdout()) {
+            eprintln!("Unable to write the man page: {}", e);
+        }
+        return;
+    }
+    let inputs = Inputs::new(matches.value_of("input-dir").map(PathBuf::from));
+    // day 4's rules, wherever they're answered from: `--schema`, or one with the inputs
+    let schema = matches
+        .value_of("schema")
+        .map(PathBuf::from)
+        .or_else(|| inputs.config(day4::SCHEMA_NAME));
+    if let Some(file) = schema {
+        match day4::Schema::read(&file) {
+            Ok(schema) => day4::use_schema(schema),
+            Err(e) => {
+                eprintln!("Unable to read the passport schema: {}", e);
+                return;
+            }
+        }
+    }
+
+    if let Some(m) = matches.subcommand_matches("report") {
+        let mut cache = if m.is_present("no-cache") {
+            None
+        } else {
+            match Cache::open(Path::new(cache::DEFAULT_FILE)) {
+                Ok(cache) => Some(cache),
+                Err(e) => {
+                    eprintln!("Not using the cache: {}", e);
+                    None
+                }
+            }
+        };
+        if let Err(e) = report::report(
+            &inputs,
+            cache.as_mut(),
+            m.value_of("output").unwrap(),
+            m.value_of("html"),
+            m.value_of("answers").unwrap(),
+        ) {
+            eprintln!("Unable to write report: {}", e);
+        }
+        return;
+    }
+
+    if let Some(m) = matches.subcommand_matches("detect") {
+        let file = m.value_of("file").unwrap();
+        if let Err(e) = detect::detect(file) {
+            eprintln!("Unable to read {}: {}", file, e);
+        }
+        return;
+    }
+
+    if let Some(m) = matches.subcommand_matches("scale") {
+        let sizes = m.values_of("sizes").map(|sizes| {
+            sizes
+                .map(|s| s.parse::<usize>().expect("sizes must be numbers"))
+                .collect()
+        });
+        scale::scale(
+            m.value_of("day")
+                .unwrap()
+                .parse()
+                .expect("day must be a number"),
+            sizes,
+            m.value_of("seed")
+                .unwrap()
+                .parse()
+                .expect("seed must be a number"),
+            m.value_of("runs")
+                .unwrap()
+                .parse()
+                .expect("runs must be a number"),
+        );
+        return;
+    }
+
+    if let Some(m) = matches.subcommand_matches("leaderboard") {
+        if let Err(e) = leaderboard::leaderboard(m.value_of("file").unwrap(), m.is_present("json"))
+        {
+            eprintln!("{}", e);
+        }
+        return;
+    }
+
+    #[cfg(feature = "bench-history")]
+    {
+        if let Some(m) = matches.subcommand_matches("bench-history") {
+            let days = m.values_of("days").map(|days| {
+                days.map(|d| d.parse::<usize>().expect("days must be numbers"))
+                    .collect()
+            });
+            match bench_history::bench_history(
+                &inputs,
+                m.value_of("history").unwrap(),
+                m.value_of("runs")
+                    .unwrap()
+                    .parse()
+                    .expect("runs must be a number"),
+                m.value_of("threshold")
+                    .unwrap()
+                    .parse()
+                    .expect("threshold must be a number"),
+                days,
+            ) {
+                Ok(true) => std::process::exit(1),
+                Ok(false) => (),
+                Err(e) => eprintln!("Unable to update bench history: {}", e),
+            }
+            return;
+        }
+    }
+
+    if matches.is_present("profile-phases") {
+        let only = matches
+            .value_of("day")
+            .map(|d| d.parse::<usize>().expect("day must be a number"));
+        let days = registry::days()
+            .into_iter()
+            .filter(|d| only.is_none_or(|day| d.day == day))
+            .collect::<Vec<registry::Day>>();
+        if days.is_empty() {
+            println!("Invalid day specified");
+        }
+        for (i, d) in days.iter().enumerate() {
+            let input = match inputs.get(d.day) {
+                Ok(input) => input,
+                Err(e) => {
+                    eprintln!("Skipping day {}: {}", d.day, e);
+                    continue;
+                }
+            };
+            let name = format!("day {}", d.day);
+            let (_, tree) = profile::record(&name, || (d.solve)(&input));
+            if i > 0 {
+                println!();
+            }
+            print!("{}", profile::format_tree(&tree));
+        }
+        return;
+    }
+
+    let day = matches.value_of("day").expect("day not specfied");
+    let input = match day.parse::<usize>().map(|d| inputs.get(d)) {
+        Ok(Ok(input)) => input,
+        Ok(Err(e)) => {
+            eprintln!("Unable to load the input for day {}: {}", day, e);
+            return;
+        }
+        Err(_) => {
+            println!("Invalid day specified");
+            return;
+        }
+    };
+    let input = &*input;
+    let slopes = match matches.values_of("slope") {
+        Some(specs) => specs.map(str::parse).collect::<Result<Vec<_>, String>>(),
+        None => Ok(day3::SLOPES.to_vec()),
+    };
+    let start = matches
+        .value_of("start")
+        .map_or(Ok((0, 0)), day3::Ride::parse_start);
+    let (slopes, ride) = match (day, slopes, start) {
+        ("3", Err(e), _) | ("3", _, Err(e)) => {
+            println!("{}", e);
+            return;
+        }
+        (_, slopes, start) => (
+            slopes.unwrap_or_default(),
+            day3::Ride {
+                start: start.unwrap_or_default(),
+                wrap_vertically: matches.is_present("wrap-vertically"),
+            },
+        ),
+    };
+    match day {
+        "1" => {
+            let target = matches
+                .value_of("target")
+                .unwrap()
+                .parse::<i64>()
+                .expect("target must be a number");
+            let ks = match matches.values_of("k") {
+                Some(ks) => ks.map(|k| k.parse().expect("k must be a number")).collect(),
+                None => vec![2, 3],
+            };
+            let mode = if matches.is_present("all") {
+                day1::Mode::All
+            } else if matches.is_present("count") {
+                day1::Mode::Count
+            } else {
+                day1::Mode::First
+            };
+            day1::day1(input, target, &ks, mode)
+        }
+        "2" if matches.is_present("generate") => day2::print_generated(
+            matches
+                .value_of("generate")
+                .unwrap()
+                .parse()
+                .expect("generate must be a number of lines"),
+            matches
+                .value_of("seed")
+                .unwrap()
+                .parse()
+                .expect("seed must be a number"),
+        ),
+        "2" => {
+            let specs = match matches.values_of("policy") {
+                Some(specs) => specs.collect(),
+                None => vec!["count", "position"],
+            };
+            let policies = specs
+                .into_iter()
+                .map(day2::policy)
+                .collect::<Result<Vec<_>, String>>();
+            match policies {
+                Ok(policies) => day2::day2(input, &policies, matches.is_present("verdicts")),
+                Err(e) => println!("{}", e),
+            }
+        }
+        "3" => {
+            let optimise = matches
+                .value_of("optimise")
+                .map(|k| k.parse().expect("optimise needs a number of slopes"));
+            day3::day3(input, &slopes, ride, optimise);
+            if matches.is_present("draw") {
+                let drawn = day3::parse_marks(matches.value_of("marks").unwrap())
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+                    .and_then(|marks| {
+                        day3::draw(
+                            input,
+                            &slopes,
+                            ride,
+                            &marks,
+                            matches.is_present("color"),
+                            matches.value_of("draw").map(Path::new),
+                        )
+                    });
+                if let Err(e) = drawn {
+                    eprintln!("Unable to draw the map: {}", e);
+                }
+            }
+        }
+        "4" => day4::day4(input),
+        "5" => day5::day5(input),
+        "6" => day6::day6(input),
+        "7" => day7::day7(input),
+        "8" => day8::day8(input),
+        "9" => day9::day9(input),
+        "10" => day10::day10(input),
+        "11" => day11::day11(input),
+        "12" => day12::day12(input),
+        "13" => day13::day13(input),
+        "14" => day14::day14(input),
+        "15" => days::day15::day15(input),
+        "16" => days::day16::day16(input),
+        "17" => days::day17::day17(input),
+        "18" => days::day18::day18(input),
+        "19" => days::day19::day19(input),
         _ => println!("Invalid day specified"),
     }
+
+    if let Some(dir) = matches.value_of("export") {
+        let dir = Path::new(dir);
+        let format = matches
+            .value_of("format")
+            .unwrap()
+            .parse::<render::ImageFormat>()
+            .unwrap();
+        let scale = matches
+            .value_of("scale")
+            .unwrap()
+            .parse::<usize>()
+            .expect("scale must be a positive number");
+        let exported = match day {
+            "3" => day3::export(input, &slopes, ride, dir, format, scale),
+            "11" => day11::export(input, dir, format, scale),
+            "17" => days::day17::export(input, dir, format, scale),
+            _ => {
+                println!("No image export for day {}", day);
+                return;
+            }
+        };
+        match exported {
+            Ok(_) => println!("Exported images to {}", dir.display()),
+            Err(e) => eprintln!("Image export failed: {}", e),
+        }
+    }
+}
+
+mod day1 {
+    use crate::parse;
+    use crate::registry::{self, Outcome};
+    use std::collections::{HashMap, HashSet};
+    use std::convert::TryFrom;
+    use std::ops::{ControlFlow, Range};
+
+    // the histogram count needs a table this wide, so past it counting always searches
+    const MAX_TABLE_TARGET: i64 = 1 << 20;
+
+    pub enum Mode {
+        First, // the first combination's product, as the puzzle asks
+        All,   // every combination
+        Count, // just how many there are
+    }
+
+    pub fn day1(input: &str, target: i64, ks: &[usize], mode: Mode) {
+        let nums = match parse::numbers::<i64>(input) {
+            Ok(nums) => nums,
+            Err(e) => {
+                println!("day 1 input: {}", e);
+                return;
+            }
+        };
+
+        for &k in ks {
+            match mode {
+                Mode::First => match first_k_sum(&nums, k, target) {
+                    Some(indices) => println!(
+                        "product of {} numbers that sum to {}: {}",
+                        k,
+                        target,
+                        describe_product(product(&nums, &indices))
+                    ),
+                    None => println!("no {} numbers sum to {}", k, target),
+                },
+                Mode::All => {
+                    let all = combinations(&nums, k, target);
+                    println!("{} numbers that sum to {}: {}", k, target, all.len());
+                    for c in all {
+                        let terms = c
+                            .indices
+                            .iter()
+                            .zip(&c.values)
+                            .map(|(i, v)| format!("{} (line {})", v, i + 1))
+                            .collect::<Vec<String>>();
+                        println!(
+                            "  {}, product {}",
+                            terms.join(" + "),
+                            describe_product(c.product)
+                        );
+                    }
+                }
+                Mode::Count => println!(
+                    "{} numbers that sum to {}: {}",
+                    k,
+                    target,
+                    count_k_sum(&nums, k, target)
+                ),
+            }
+        }
+    }
+
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
+
+    fn part1(nums: &[i64]) -> String {
+        first_product(nums, 2)
+    }
+
+    fn part2(nums: &[i64]) -> String {
+        first_product(nums, 3)
+    }
+
+    fn parse(input: &str) -> Vec<i64> {
+        parse::numbers(input).unwrap_or_else(|e| panic!("day 1 input: {}", e))
+    }
+
+    // None if it doesn't fit in an i64
+    fn product(nums: &[i64], indices: &[usize]) -> Option<i64> {
+        indices
+            .iter()
+            .try_fold(1i64, |product, &i| product.checked_mul(nums[i]))
+    }
+
+    fn describe_product(product: Option<i64>) -> String {
+        product.map_or(String::from("(overflows i64)"), |p| p.to_string())
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Combination {
+        pub indices: Vec<usize>,
+        pub values: Vec<i64>,
+        pub product: Option<i64>,
+    }
+
+    pub fn combinations(nums: &[i64], k: usize, target: i64) -> Vec<Combination> {
+        k_sum(nums, k, target)
+            .into_iter()
+            .map(|indices| Combination {
+                values: indices.iter().map(|&i| nums[i]).collect(),
+                product: product(nums, &indices),
+                indices,
+            })
+            .collect()
+    }
+
+    fn first_product(nums: &[i64], k: usize) -> String {
+        first_k_sum(nums, k, 2020)
+            .and_then(|indices| product(nums, &indices))
+            .map_or(String::from("none"), |p| p.to_string())
+    }
+
+    // one of `k_sum`'s tuples, if there are any, found without looking for the rest
+    pub fn first_k_sum(nums: &[i64], k: usize, target: i64) -> Option<Vec<usize>> {
+        search(nums, k, target as i128, &mut ControlFlow::Break).break_value()
+    }
+
+    // Every set of k distinct positions in `nums` whose values add up to `target`, as ascending
+    // index tuples in ascending order. Equal values at different positions are different
+    // entries, so [1010, 1010] has one pair for 2020 but [1010] has none. Sums are taken in
+    // i128, so values near the ends of i64 don't overflow.
+    pub fn k_sum(nums: &[i64], k: usize, target: i64) -> Vec<Vec<usize>> {
+        let mut found = Vec::new();
+        let _ = search(nums, k, target as i128, &mut |indices| {
+            found.push(indices);
+            ControlFlow::<()>::Continue(())
+        });
+        found.sort_unstable();
+        found
+    }
+
+    // Calls `visit` with each of `k_sum`'s tuples, in no particular order, until it breaks.
+    fn search<B>(
+        nums: &[i64],
+        k: usize,
+        target: i128,
+        visit: &mut dyn FnMut(Vec<usize>) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        match k {
+            0 if target == 0 => visit(vec![]),
+            0 => ControlFlow::Continue(()),
+            1 => {
+                for i in (0..nums.len()).filter(|&i| nums[i] as i128 == target) {
+                    visit(vec![i])?;
+                }
+                ControlFlow::Continue(())
+            }
+            2 => two_sum(nums, target, visit),
+            _ => sorted_k_sum(
+                nums,
+                &sorted_indices(nums),
+                k,
+                target,
+                &mut Vec::new(),
+                &mut |mut indices| {
+                    indices.sort_unstable();
+                    visit(indices)
+                },
+            ),
+        }
+    }
+
+    // How many tuples `k_sum` would return, without making them.
+    pub fn count_k_sum(nums: &[i64], k: usize, target: i64) -> u128 {
+        if table_is_cheaper(nums, k, target) {
+            return count_with_table(nums, k, target as usize);
+        }
+        let target = target as i128;
+        match k {
+            0 => (target == 0) as u128,
+            1 => nums.iter().filter(|&&n| n as i128 == target).count() as u128,
+            2 => {
+                let mut seen: HashMap<i64, u128> = HashMap::new();
+                let mut count = 0;
+                for &n in nums {
+                    if let Ok(complement) = i64::try_from(target - n as i128) {
+                        count += seen.get(&complement).copied().unwrap_or(0);
+                    }
+                    *seen.entry(n).or_default() += 1;
+                }
+                count
+            }
+            _ => count_sorted(nums, &sorted_indices(nums), k, target),
+        }
+    }
+
+    // Rough step counts for the two ways of counting: the table does k steps per sum up to the
+    // target for each distinct value, while hashing pairs is a step per entry and the sorted
+    // search a pass over the list per choice of all but two. The table wins on long lists of
+    // few values with a small target, and only works without negatives.
+    fn table_is_cheaper(nums: &[i64], k: usize, target: i64) -> bool {
+        if k < 2 || !(0..=MAX_TABLE_TARGET).contains(&target) || nums.iter().any(|&n| n < 0) {
+            return false;
+        }
+        let n = nums.len() as u128;
+        let search = match k {
+            2 => n,
+            _ => n.saturating_pow(k as u32 - 1),
+        };
+        let distinct = nums
+            .iter()
+            .filter(|&&n| n <= target)
+            .collect::<HashSet<_>>()
+            .len() as u128;
+        distinct * (target as u128 + 1) * k as u128 <= search
+    }
+
+    // With no negative values, only values up to the target can take part, so this works on a
+    // histogram of those: ways[j][s] counts the ways to pick j positions adding up to s among
+    // the values seen so far. Past the histogram, the work depends on how many distinct values
+    // there are and not how often each appears.
+    fn count_with_table(nums: &[i64], k: usize, target: usize) -> u128 {
+        let mut counts = vec![0u128; target + 1];
+        for &n in nums.iter().filter(|&&n| n as usize <= target) {
+            counts[n as usize] += 1;
+        }
+        let mut ways = vec![vec![0u128; target + 1]; k + 1];
+        ways[0][0] = 1;
+        for (value, &count) in counts.iter().enumerate().filter(|(_, &c)| c > 0) {
+            // take the larger counts first so this value's updates only read older ones
+            for j in (1..=k).rev() {
+                for sum in (0..=target).rev() {
+                    // choose t of the `count` copies: C(count, t) ways
+                    let mut choose = 1u128;
+                    let mut extra = 0;
+                    for t in 1..=j.min(count as usize) {
+                        choose = choose * (count - t as u128 + 1) / t as u128;
+                        match sum.checked_sub(t * value) {
+                            Some(rest) => extra += ways[j - t][rest] * choose,
+                            None => break,
+                        }
+                    }
+                    ways[j][sum] += extra;
+                }
+            }
+        }
+        ways[k][target]
+    }
+
+    fn sorted_indices(nums: &[i64]) -> Vec<usize> {
+        let mut sorted = (0..nums.len()).collect::<Vec<usize>>();
+        sorted.sort_by_key(|&i| nums[i]);
+        sorted
+    }
+
+    // One pass, looking each number's complement up among the ones before it. Equal values are
+    // chained through `previous` rather than kept in a Vec per value.
+    fn two_sum<B>(
+        nums: &[i64],
+        target: i128,
+        visit: &mut dyn FnMut(Vec<usize>) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        let mut last: HashMap<i64, usize> = HashMap::new();
+        let mut previous = vec![None; nums.len()];
+        for (j, &n) in nums.iter().enumerate() {
+            // a complement outside i64 can't be in the list
+            if let Ok(complement) = i64::try_from(target - n as i128) {
+                let mut earlier = last.get(&complement).copied();
+                while let Some(i) = earlier {
+                    visit(vec![i, j])?;
+                    earlier = previous[i];
+                }
+            }
+            previous[j] = last.insert(n, j);
+        }
+        ControlFlow::Continue(())
+    }
+
+    // `sorted` holds indices into `nums` ordered by value. Fixes the smallest remaining number
+    // and goes one level deeper until two are left, which the two pointers find; the depth is
+    // k, not the length of the list.
+    fn sorted_k_sum<B>(
+        nums: &[i64],
+        sorted: &[usize],
+        k: usize,
+        target: i128,
+        chosen: &mut Vec<usize>,
+        visit: &mut dyn FnMut(Vec<usize>) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        if k == 2 {
+            return pair_runs(nums, sorted, target, |low, high| {
+                for i in low.clone() {
+                    // within one run, each pair once
+                    let from = if low == high { i + 1 } else { high.start };
+                    for j in from..high.end {
+                        let mut indices = chosen.clone();
+                        indices.extend([sorted[i], sorted[j]]);
+                        visit(indices)?;
+                    }
+                }
+                ControlFlow::Continue(())
+            });
+        }
+        for p in 0..sorted.len().saturating_sub(k - 1) {
+            let n = nums[sorted[p]] as i128;
+            // everything after is at least n, so k of them already overshoot
+            if n * k as i128 > target {
+                break;
+            }
+            chosen.push(sorted[p]);
+            sorted_k_sum(nums, &sorted[p + 1..], k - 1, target - n, chosen, visit)?;
+            chosen.pop();
+        }
+        ControlFlow::Continue(())
+    }
+
+    // `sorted_k_sum` without making the tuples; pairs are counted a run at a time
+    fn count_sorted(nums: &[i64], sorted: &[usize], k: usize, target: i128) -> u128 {
+        if k == 2 {
+            let mut count = 0;
+            let _ = pair_runs(nums, sorted, target, |low, high| {
+                let m = low.len() as u128;
+                count += if low == high {
+                    m * (m - 1) / 2
+                } else {
+                    m * high.len() as u128
+                };
+                ControlFlow::<()>::Continue(())
+            });
+            return count;
+        }
+        let mut count = 0;
+        for p in 0..sorted.len().saturating_sub(k - 1) {
+            let n = nums[sorted[p]] as i128;
+            if n * k as i128 > target {
+                break;
+            }
+            count += count_sorted(nums, &sorted[p + 1..], k - 1, target - n);
+        }
+        count
+    }
+
+    // The pairs of positions in `sorted` adding up to `target`, a run of equal values at a time
+    // so duplicates cost nothing until they're wanted one by one: `runs(low, high)` means every
+    // position in `low` pairs with every one in `high`, or with both the same range, every two
+    // positions in it do. Stops when `runs` breaks.
+    fn pair_runs<B>(
+        nums: &[i64],
+        sorted: &[usize],
+        target: i128,
+        mut runs: impl FnMut(Range<usize>, Range<usize>) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        let value = |i: usize| nums[sorted[i]] as i128;
+        // lo..hi is what's left to look at; fewer than two and there are no pairs
+        let (mut lo, mut hi) = (0, sorted.len());
+        while hi - lo >= 2 {
+            let (a, b) = (value(lo), value(hi - 1));
+            if a + b < target {
+                lo += 1;
+            } else if a + b > target {
+                hi -= 1;
+            } else if a == b {
+                // every pair in lo..hi has the same sum
+                return runs(lo..hi, lo..hi);
+            } else {
+                let low_run = (lo..hi).take_while(|&i| value(i) == a).count();
+                let high_run = (lo..hi).rev().take_while(|&i| value(i) == b).count();
+                runs(lo..lo + low_run, hi - high_run..hi)?;
+                lo += low_run;
+                hi -= high_run;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const INPUT: [i64; 6] = [1721, 979, 366, 299, 675, 1456];
+
+        #[test]
+        fn example() {
+            assert_eq!(k_sum(&INPUT, 2, 2020), [[0, 3]]);
+            assert_eq!(k_sum(&INPUT, 3, 2020), [[1, 2, 4]]);
+            assert_eq!(part1(&INPUT), "514579");
+            assert_eq!(part2(&INPUT), "241861950");
+        }
+
+        #[test]
+        fn duplicates() {
+            assert_eq!(k_sum(&[1010, 1010], 2, 2020), [[0, 1]]);
+            assert!(k_sum(&[1010], 2, 2020).is_empty());
+            assert_eq!(k_sum(&[5, 5, 5], 2, 10), [[0, 1], [0, 2], [1, 2]]);
+            assert_eq!(k_sum(&[5, 5, 5], 3, 15), [[0, 1, 2]]);
+            assert_eq!(
+                k_sum(&[1, 2, 2, 3, 3], 3, 7),
+                [[0, 3, 4], [1, 2, 3], [1, 2, 4]]
+            );
+        }
+
+        #[test]
+        fn enumerate_and_count() {
+            let nums = [1010, 1010, 1010, 2020, 0];
+            let all = combinations(&nums, 2, 2020);
+            assert_eq!(all.len(), 4);
+            assert_eq!(
+                all[3],
+                Combination {
+                    indices: vec![3, 4],
+                    values: vec![2020, 0],
+                    product: Some(0)
+                }
+            );
+            assert_eq!(count_k_sum(&nums, 2, 2020), 4);
+            assert_eq!(count_k_sum(&nums, 3, 2020), 3);
+            // a million copies of 1010: C(10^6, 2) pairs
+            assert_eq!(
+                count_k_sum(&vec![1010; 1_000_000], 2, 2020),
+                499_999_500_000
+            );
+        }
+
+        #[test]
+        fn edge_cases() {
+            assert!(k_sum(&[], 2, 2020).is_empty());
+            assert!(k_sum(&[], 3, 0).is_empty());
+            assert!(k_sum(&[2020], 2, 2020).is_empty());
+            assert!(k_sum(&[1000, 1020], 3, 2020).is_empty());
+            assert_eq!(first_k_sum(&[2020], 1, 2020), Some(vec![0]));
+            assert_eq!(part1(&[]), "none");
+            assert_eq!(count_k_sum(&[], 3, 2020), 0);
+
+            // negative values, and ones whose sums or products leave i64
+            let nums = [-5, 2030, i64::MAX, -10, i64::MIN, 2025];
+            assert_eq!(k_sum(&nums, 2, 2020), [[0, 5], [1, 3]]);
+            assert_eq!(k_sum(&nums, 3, 2015), [[0, 1, 3]]);
+            assert_eq!(k_sum(&nums, 2, -1), [[2, 4]]);
+            assert_eq!(count_k_sum(&nums, 2, 2020), 2);
+            assert_eq!(count_k_sum(&nums, 2, -1), 1);
+            assert_eq!(product(&nums, &[2, 4]), None);
+            assert_eq!(combinations(&nums, 2, -1)[0].values, [i64::MAX, i64::MIN]);
+        }
+
+        #[test]
+        fn first_stops_early() {
+            // enumerating these would mean 5 * 10^11 pairs or 1.7 * 10^17 triples
+            let same = vec![1010; 1_000_000];
+            assert_eq!(first_k_sum(&same, 2, 2020), Some(vec![0, 1]));
+            assert_eq!(
+                first_k_sum(&vec![-1; 1_000_000], 3, -3).map(|t| t.len()),
+                Some(3)
+            );
+            assert_eq!(first_product(&same, 2), "1020100");
+            assert_eq!(first_k_sum(&same, 2, 2021), None);
+            // counting runs of equal values without pairing them up
+            assert_eq!(count_k_sum(&vec![-5; 3000], 3, -15), 4_495_501_000);
+        }
+
+        #[test]
+        fn counting_picks_the_cheaper_way() {
+            // a table 10^6 wide for each of 20,000 values would take hours; hashing is instant
+            let nums = (0..20_000).map(|n| n * 50).collect::<Vec<i64>>();
+            assert!(!table_is_cheaper(&nums, 2, 1_000_000));
+            let started = std::time::Instant::now();
+            // 50(n + m) = 10^6 with n < m < 20,000
+            assert_eq!(count_k_sum(&nums, 2, 1_000_000), 9_999);
+            assert!(
+                started.elapsed().as_secs() < 5,
+                "took {:?}",
+                started.elapsed()
+            );
+            // a few values many times over is what the table is for
+            assert!(table_is_cheaper(&vec![1010; 1_000_000], 2, 2020));
+            assert!(table_is_cheaper(&vec![7; 10_000], 3, 21));
+            assert!(!table_is_cheaper(&[1, 2, 3], 2, 3));
+        }
+
+        #[test]
+        fn large_input() {
+            // over a million numbers, half of them negative, with one pair hitting the target
+            let mut nums = (0..1_200_000)
+                .map(|n| n * 4 - 2_400_000)
+                .collect::<Vec<i64>>();
+            nums.push(1);
+            assert_eq!(k_sum(&nums, 2, 1), [[600_000, 1_200_000]]);
+            // 4(n + m) - 4_800_000 = 8, so n + m = 1_200_002 with n < m < 1_200_000
+            assert_eq!(count_k_sum(&nums, 2, 8), 599_998);
+        }
+
+        #[test]
+        fn against_brute_force() {
+            let all = [3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 8, 9, 7, 9, -4, 0, -4];
+            // without the negatives, the histogram table can count too
+            for nums in [&all[..15], &all[..]] {
+                for target in -10..30 {
+                    let mut pairs = Vec::new();
+                    let mut quads = Vec::new();
+                    for a in 0..nums.len() {
+                        for b in a + 1..nums.len() {
+                            if nums[a] + nums[b] == target {
+                                pairs.push(vec![a, b]);
+                            }
+                            for c in b + 1..nums.len() {
+                                for d in c + 1..nums.len() {
+                                    if nums[a] + nums[b] + nums[c] + nums[d] == target {
+                                        quads.push(vec![a, b, c, d]);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    assert_eq!(k_sum(nums, 2, target), pairs, "target {}", target);
+                    assert_eq!(k_sum(nums, 4, target), quads, "target {}", target);
+                    assert_eq!(count_k_sum(nums, 2, target), pairs.len() as u128);
+                    assert_eq!(count_k_sum(nums, 4, target), quads.len() as u128);
+                    if target >= 0 && nums.len() == 15 {
+                        let target = target as usize;
+                        assert_eq!(count_with_table(nums, 2, target), pairs.len() as u128);
+                        assert_eq!(count_with_table(nums, 4, target), quads.len() as u128);
+                    }
+                }
+            }
+        }
+    }
+}
+
+mod day2 {
+    use crate::parse;
+    use crate::registry::{self, Outcome};
+    use crate::scale::Rng;
+    use regex::Regex;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::str::FromStr;
+
+    // "1-3 a: abcde": two numbers and a letter that a policy interprets, and the password
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct PasswordEntry {
+        pub low: usize,
+        pub high: usize,
+        pub letter: char,
+        pub password: String,
+    }
+
+    impl FromStr for PasswordEntry {
+        type Err = EntryError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            // the number is dropped again; `entry` puts the real one in
+            fields(parse::Line { number: 0, text: s }).map_err(|e| EntryError {
+                column: e.column,
+                message: e.message,
+            })
+        }
+    }
+
+    // what's wrong with an entry on its own, without knowing which line of the input it's on
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct EntryError {
+        pub column: usize,
+        pub message: String,
+    }
+
+    impl fmt::Display for EntryError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "column {}: {}", self.column, self.message)
+        }
+    }
+
+    impl PasswordEntry {
+        // the character at a 1-based position, if there is one
+        fn at(&self, position: usize) -> Option<char> {
+            position
+                .checked_sub(1)
+                .and_then(|i| self.password.chars().nth(i))
+        }
+
+        fn positions(&self) -> [usize; 2] {
+            [self.low, self.high]
+        }
+
+        fn holds_letter(&self, position: usize) -> bool {
+            self.at(position) == Some(self.letter)
+        }
+
+        // how many of positions `low` and `high` hold the letter
+        fn positions_held(&self) -> usize {
+            self.positions()
+                .iter()
+                .filter(|&&p| self.holds_letter(p))
+                .count()
+        }
+
+        // what's at a position, for diagnostics: "position 3 is 'c'"
+        fn describe(&self, position: usize) -> String {
+            match self.at(position) {
+                Some(c) => format!("position {} is {:?}", position, c),
+                None if position == 0 => String::from("position 0 doesn't exist (they start at 1)"),
+                None => format!(
+                    "position {} is past the end of the {}-letter password",
+                    position,
+                    self.password.chars().count()
+                ),
+            }
+        }
+
+        // the positions that don't hold the letter, described
+        fn misses(&self) -> String {
+            self.positions()
+                .iter()
+                .filter(|&&p| !self.holds_letter(p))
+                .map(|&p| self.describe(p))
+                .collect::<Vec<String>>()
+                .join(", ")
+        }
+    }
+
+    pub trait PasswordPolicy {
+        fn name(&self) -> String;
+
+        // Ok, or why the entry breaks the policy
+        fn check(&self, entry: &PasswordEntry) -> Result<(), String>;
+
+        fn is_valid(&self, entry: &PasswordEntry) -> bool {
+            self.check(entry).is_ok()
+        }
+    }
+
+    // part 1: the letter appears between low and high times
+    pub struct CountInRange;
+
+    impl PasswordPolicy for CountInRange {
+        fn name(&self) -> String {
+            String::from("count")
+        }
+
+        fn check(&self, entry: &PasswordEntry) -> Result<(), String> {
+            if entry.low > entry.high {
+                return Err(format!(
+                    "{}-{} runs backwards, so no count is allowed",
+                    entry.low, entry.high
+                ));
+            }
+            let count = entry
+                .password
+                .chars()
+                .filter(|&c| c == entry.letter)
+                .count();
+            if (entry.low..=entry.high).contains(&count) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{:?} appears {} time{}, allowed {}-{}",
+                    entry.letter,
+                    count,
+                    if count == 1 { "" } else { "s" },
+                    entry.low,
+                    entry.high
+                ))
+            }
+        }
+    }
+
+    // part 2: exactly one of positions low and high (1-based) holds the letter
+    pub struct ExactlyOnePosition;
+
+    impl PasswordPolicy for ExactlyOnePosition {
+        fn name(&self) -> String {
+            String::from("position")
+        }
+
+        fn check(&self, entry: &PasswordEntry) -> Result<(), String> {
+            match entry.positions_held() {
+                1 => Ok(()),
+                0 => Err(format!(
+                    "{:?} is in neither: {}",
+                    entry.letter,
+                    entry.misses()
+                )),
+                _ => Err(format!(
+                    "{:?} is at both {} and {}",
+                    entry.letter, entry.low, entry.high
+                )),
+            }
+        }
+    }
+
+    // at least n of positions low and high hold the letter
+    pub struct AtLeastPositions(pub usize);
+
+    impl PasswordPolicy for AtLeastPositions {
+        fn name(&self) -> String {
+            format!("at-least:{}", self.0)
+        }
+
+        fn check(&self, entry: &PasswordEntry) -> Result<(), String> {
+            let held = entry.positions_held();
+            if held >= self.0 {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{:?} is at {} of positions {} and {}, needs {}: {}",
+                    entry.letter,
+                    held,
+                    entry.low,
+                    entry.high,
+                    self.0,
+                    entry.misses()
+                ))
+            }
+        }
+    }
+
+    // none of these characters appear in the password
+    pub struct Forbidden(pub String);
+
+    impl PasswordPolicy for Forbidden {
+        fn name(&self) -> String {
+            format!("forbid:{}", self.0)
+        }
+
+        fn check(&self, entry: &PasswordEntry) -> Result<(), String> {
+            match entry
+                .password
+                .chars()
+                .enumerate()
+                .find(|(_, c)| self.0.contains(*c))
+            {
+                Some((i, c)) => Err(format!("has forbidden {:?} at position {}", c, i + 1)),
+                None => Ok(()),
+            }
+        }
+    }
+
+    // The password matches a regex, in which {letter}, {low} and {high} stand for the entry's
+    // values, e.g. `^{letter}` for "starts with the letter". Each filled-in regex is compiled
+    // once and kept, keyed by only the values the pattern uses, so a pattern without
+    // placeholders compiles once in all.
+    pub struct Pattern {
+        pattern: String,
+        compiled: RefCell<HashMap<Filled, Regex>>,
+    }
+
+    // the letter, low and high a regex was filled in with, each if the pattern uses it
+    type Filled = (Option<char>, Option<usize>, Option<usize>);
+
+    impl Pattern {
+        pub fn new(pattern: &str) -> Result<Pattern, String> {
+            let pattern = Pattern {
+                pattern: String::from(pattern),
+                compiled: RefCell::new(HashMap::new()),
+            };
+            pattern.regex('a', 1, 1).map_err(|e| e.to_string())?;
+            Ok(pattern)
+        }
+
+        fn regex(&self, letter: char, low: usize, high: usize) -> Result<Regex, regex::Error> {
+            let uses = |placeholder| self.pattern.contains(placeholder);
+            let key = (
+                Some(letter).filter(|_| uses("{letter}")),
+                Some(low).filter(|_| uses("{low}")),
+                Some(high).filter(|_| uses("{high}")),
+            );
+            if let Some(re) = self.compiled.borrow().get(&key) {
+                // cloning a Regex shares the compiled program
+                return Ok(re.clone());
+            }
+            let source = self
+                .pattern
+                .replace("{letter}", &regex::escape(&letter.to_string()))
+                .replace("{low}", &low.to_string())
+                .replace("{high}", &high.to_string());
+            let re = Regex::new(&source)?;
+            self.compiled.borrow_mut().insert(key, re.clone());
+            Ok(re)
+        }
+    }
+
+    impl PasswordPolicy for Pattern {
+        fn name(&self) -> String {
+            format!("regex:{}", self.pattern)
+        }
+
+        fn check(&self, entry: &PasswordEntry) -> Result<(), String> {
+            // `new` checked the pattern, but a huge {low} could still make it too big
+            let re = self
+                .regex(entry.letter, entry.low, entry.high)
+                .map_err(|e| e.to_string())?;
+            if re.is_match(&entry.password) {
+                Ok(())
+            } else {
+                Err(format!("doesn't match {}", re.as_str()))
+            }
+        }
+    }
+
+    pub const POLICY_HELP: &str =
+        "count, position, at-least:N, forbid:CHARS or regex:PATTERN ({letter}, {low} and {high} \
+         stand for the line's values)";
+
+    // a policy from its name on the command line
+    pub fn policy(spec: &str) -> Result<Box<dyn PasswordPolicy>, String> {
+        let (kind, arg) = match spec.find(':') {
+            Some(i) => (&spec[..i], Some(&spec[i + 1..])),
+            None => (spec, None),
+        };
+        match (kind, arg) {
+            ("count", None) => Ok(Box::new(CountInRange)),
+            ("position", None) => Ok(Box::new(ExactlyOnePosition)),
+            ("at-least", Some(n)) => n
+                .parse()
+                .map(|n| Box::new(AtLeastPositions(n)) as Box<dyn PasswordPolicy>)
+                .map_err(|_| format!("at-least needs a number, not {:?}", n)),
+            ("forbid", Some(chars)) if !chars.is_empty() => {
+                Ok(Box::new(Forbidden(String::from(chars))))
+            }
+            ("regex", Some(pattern)) => Pattern::new(pattern)
+                .map(|p| Box::new(p) as Box<dyn PasswordPolicy>)
+                .map_err(|e| format!("bad regex: {}", e)),
+            _ => Err(format!("unknown policy {:?}; use {}", spec, POLICY_HELP)),
+        }
+    }
+
+    pub fn day2(input: &str, policies: &[Box<dyn PasswordPolicy>], verdicts: bool) {
+        // unlike `solve`, keep going past bad lines and say what's wrong with them
+        let lines = parse::lines(input)
+            .map(|line| (line, entry(line)))
+            .collect::<Vec<_>>();
+        let entries = lines
+            .iter()
+            .filter_map(|(_, entry)| entry.as_ref().ok().cloned())
+            .collect::<Vec<PasswordEntry>>();
+        let names = policies.iter().map(|p| p.name()).collect::<Vec<String>>();
+        let width = names.iter().map(|n| n.len()).max().unwrap_or(0).max(6);
+
+        if verdicts {
+            let entry_width = lines.iter().map(|(l, _)| l.text.len()).max().unwrap_or(0);
+            print!("{:>5}  {:<w$}", "line", "entry", w = entry_width);
+            for name in &names {
+                print!("  {:>w$}", name, w = width);
+            }
+            println!();
+            for (line, entry) in &lines {
+                print!("{:>5}  {:<w$}", line.number, line.text, w = entry_width);
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        println!("  malformed: {}", e.message);
+                        continue;
+                    }
+                };
+                let checks = policies
+                    .iter()
+                    .map(|p| p.check(entry))
+                    .collect::<Vec<Result<(), String>>>();
+                for check in &checks {
+                    let verdict = if check.is_ok() { "valid" } else { "-" };
+                    print!("  {:>w$}", verdict, w = width);
+                }
+                println!();
+                for (name, check) in names.iter().zip(&checks) {
+                    if let Err(why) = check {
+                        println!("{:>7}{}: {}", "", name, why);
+                    }
+                }
+            }
+            println!();
+        } else {
+            for (_, entry) in &lines {
+                if let Err(e) = entry {
+                    println!("skipping {}", e);
+                }
+            }
+        }
+
+        println!("{:<w$}  valid", "policy", w = width);
+        for (policy, name) in policies.iter().zip(&names) {
+            println!(
+                "{:<w$}  {} of {}",
+                name,
+                count_valid(&entries, policy.as_ref()),
+                entries.len(),
+                w = width
+            );
+        }
+    }
+
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
+
+    fn part1(entries: &[PasswordEntry]) -> String {
+        count_valid(entries, &CountInRange).to_string()
+    }
+
+    fn part2(entries: &[PasswordEntry]) -> String {
+        count_valid(entries, &ExactlyOnePosition).to_string()
+    }
+
+    pub fn count_valid(entries: &[PasswordEntry], policy: &dyn PasswordPolicy) -> usize {
+        entries.iter().filter(|e| policy.is_valid(e)).count()
+    }
+
+    fn parse(input: &str) -> Vec<PasswordEntry> {
+        parse::lines(input)
+            .map(|line| entry(line).unwrap_or_else(|e| panic!("day 2 input: {}", e)))
+            .collect()
+    }
+
+    fn entry(line: parse::Line) -> parse::Result<PasswordEntry> {
+        line.text
+            .parse()
+            .map_err(|e: EntryError| parse::ParseError {
+                line: line.number,
+                column: e.column,
+                message: e.message,
+            })
+    }
+
+    // "1-2 a: asdf". The numbers aren't checked against the password or each other here, since
+    // each policy reads them its own way; the policies say what's wrong instead.
+    fn fields(line: parse::Line) -> parse::Result<PasswordEntry> {
+        let (policy, password) = line.key_value(":")?;
+        let (low, high, letter) = rule(line, policy)?;
+        if password.is_empty() || password.contains(char::is_whitespace) {
+            return Err(line.error(
+                password,
+                format!("expected one word of password, found {:?}", password),
+            ));
+        }
+        Ok(PasswordEntry {
+            low,
+            high,
+            letter,
+            password: String::from(password),
+        })
+    }
+
+    // "1-3 a", the part before the password: (low, high, letter)
+    fn rule<'a>(line: parse::Line<'a>, part: &'a str) -> parse::Result<(usize, usize, char)> {
+        let (range, letter) = line.split_once(part, " ")?;
+        let (low, high) = line.split_once(range, "-")?;
+        let letter = match letter.chars().collect::<Vec<char>>()[..] {
+            [c] => c,
+            _ => return Err(line.error(letter, "expected a single letter")),
+        };
+        Ok((line.parse_at(low)?, line.parse_at(high)?, letter))
+    }
+
+    // which of the two puzzle policies a generated password should pass
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Wanted {
+        pub count: bool,
+        pub position: bool,
+    }
+
+    impl Wanted {
+        pub const ALL: [Wanted; 4] = [
+            Wanted {
+                count: true,
+                position: true,
+            },
+            Wanted {
+                count: true,
+                position: false,
+            },
+            Wanted {
+                count: false,
+                position: true,
+            },
+            Wanted {
+                count: false,
+                position: false,
+            },
+        ];
+    }
+
+    // A password for rule `spec` ("1-3 a") that passes or fails each policy as wanted. Some
+    // combinations can't happen, like passing the position policy with "2-2 a", and those are
+    // an error.
+    pub fn password_for(spec: &str, wanted: Wanted, rng: &mut Rng) -> Result<String, String> {
+        let line = parse::Line {
+            number: 1,
+            text: spec.trim(),
+        };
+        let (low, high, letter) = rule(line, line.text).map_err(|e| e.message)?;
+        build_password(low, high, letter, wanted, rng).ok_or_else(|| {
+            format!(
+                "no password for {:?} can be {} by count and {} by position",
+                spec,
+                if wanted.count { "valid" } else { "invalid" },
+                if wanted.position { "valid" } else { "invalid" }
+            )
+        })
+    }
+
+    // Settles which of the two positions hold the letter and how often it appears in all, then
+    // fills in the rest at random. None only when nothing can pass: the position policy needs
+    // two different positions, one of them not 0, and the count policy needs low <= high.
+    fn build_password(
+        low: usize,
+        high: usize,
+        letter: char,
+        wanted: Wanted,
+        rng: &mut Rng,
+    ) -> Option<String> {
+        let both = if low == high {
+            vec![low]
+        } else {
+            vec![low, high]
+        };
+        let held = if wanted.position {
+            // a repeated position counts twice, and position 0 is never held
+            let real = both
+                .iter()
+                .copied()
+                .filter(|&p| p > 0)
+                .collect::<Vec<usize>>();
+            if low == high || real.is_empty() {
+                return None;
+            }
+            vec![real[rng.range(0, real.len())]]
+        } else if !both.contains(&0) && (!wanted.count || both.len() <= high) && rng.chance(50) {
+            both
+        } else {
+            vec![]
+        };
+        let count = if wanted.count {
+            if low > high {
+                return None;
+            }
+            let least = low.max(held.len());
+            rng.range(least, high.min(least + 3) + 1)
+        } else if low > held.len() && rng.chance(50) {
+            rng.range(held.len(), low)
+        } else {
+            let least = (high + 1).max(held.len());
+            rng.range(least, least + 3)
+        };
+        // the extra letters go anywhere but the two positions, and there are enough places
+        let extra = count - held.len();
+        let length = held.iter().copied().max().unwrap_or(0).max(extra + 2) + rng.range(0, 5);
+
+        let filler = (b'a'..=b'z')
+            .map(char::from)
+            .filter(|&c| c != letter)
+            .collect::<Vec<char>>();
+        let mut password = (0..length)
+            .map(|_| filler[rng.range(0, filler.len())])
+            .collect::<Vec<char>>();
+        let mut free = (1..=length)
+            .filter(|p| *p != low && *p != high)
+            .collect::<Vec<usize>>();
+        rng.shuffle(&mut free);
+        for &p in held.iter().chain(free.iter().take(extra)) {
+            password[p - 1] = letter;
+        }
+
+        let entry = PasswordEntry {
+            low,
+            high,
+            letter,
+            password: password.into_iter().collect(),
+        };
+        debug_assert_eq!(
+            (
+                CountInRange.is_valid(&entry),
+                ExactlyOnePosition.is_valid(&entry)
+            ),
+            (wanted.count, wanted.position),
+            "{:?}",
+            entry
+        );
+        Some(entry.password)
+    }
+
+    // a whole puzzle input and the answers it should give
+    #[derive(Debug)]
+    pub struct GeneratedInput {
+        pub input: String,
+        pub valid_by_count: usize,
+        pub valid_by_position: usize,
+    }
+
+    // `mix` says how many lines to make of each kind; the lines come out shuffled
+    pub fn generate_input(mix: &[(Wanted, usize)], rng: &mut Rng) -> GeneratedInput {
+        let mut lines = Vec::new();
+        let (mut valid_by_count, mut valid_by_position) = (0, 0);
+        for &(wanted, n) in mix {
+            for _ in 0..n {
+                // two different positions, neither 0, so every combination has a password
+                let low = rng.range(1, 8);
+                let spec = format!(
+                    "{}-{} {}",
+                    low,
+                    rng.range(low + 1, low + 12),
+                    char::from(b'a' + rng.range(0, 26) as u8)
+                );
+                let password = password_for(&spec, wanted, rng).expect("a possible rule");
+                lines.push(format!("{}: {}", spec, password));
+            }
+            valid_by_count += n * wanted.count as usize;
+            valid_by_position += n * wanted.position as usize;
+        }
+        rng.shuffle(&mut lines);
+        GeneratedInput {
+            input: lines.into_iter().map(|l| l + "\n").collect(),
+            valid_by_count,
+            valid_by_position,
+        }
+    }
+
+    // `size` lines, a quarter of each kind
+    fn even_mix(size: usize) -> Vec<(Wanted, usize)> {
+        Wanted::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| (w, size / 4 + (i < size % 4) as usize))
+            .collect()
+    }
+
+    // for `scale`
+    pub fn generate(size: usize, rng: &mut Rng) -> String {
+        generate_input(&even_mix(size), rng).input
+    }
+
+    // an input to stdout and its answers to stderr, so `> file` keeps just the input
+    pub fn print_generated(size: usize, seed: u64) {
+        let generated = generate_input(&even_mix(size), &mut Rng::new(seed));
+        print!("{}", generated.input);
+        eprintln!(
+            "expected: {} valid by count, {} valid by position",
+            generated.valid_by_count, generated.valid_by_position
+        );
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const INPUT: &str = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n";
+
+        #[test]
+        fn example() {
+            let entries = parse(INPUT);
+            assert_eq!(part1(&entries), "2");
+            assert_eq!(part2(&entries), "1");
+        }
+
+        #[test]
+        fn policies() {
+            let entries = parse(INPUT);
+            let valid = |spec: &str| {
+                let policy = policy(spec).unwrap();
+                entries
+                    .iter()
+                    .map(|e| policy.is_valid(e))
+                    .collect::<Vec<bool>>()
+            };
+            assert_eq!(valid("at-least:1"), [true, false, true]);
+            assert_eq!(valid("at-least:2"), [false, false, true]);
+            assert_eq!(valid("forbid:a"), [false, true, true]);
+            assert_eq!(valid("regex:^{letter}"), [true, false, true]);
+            assert_eq!(valid("regex:^.{{low},{high}}$"), [false, false, true]);
+            assert_eq!(policy("position").unwrap().name(), "position");
+            assert!(policy("regex:(").is_err());
+            assert!(policy("at-least:x").is_err());
+            assert!(policy("lenient").is_err());
+        }
+
+        #[test]
+        fn patterns_compile_once() {
+            let entries = parse(INPUT);
+            let compiled = |pattern: &str| {
+                let pattern = Pattern::new(pattern).unwrap();
+                entries.iter().for_each(|e| drop(pattern.check(e)));
+                let count = pattern.compiled.borrow().len();
+                count
+            };
+            // `new` compiles one to check the pattern
+            assert_eq!(compiled("^[a-z]+$"), 1);
+            assert_eq!(compiled("^{letter}"), 3);
+            assert_eq!(compiled("^.{{low},{high}}$"), 3);
+            assert_eq!(compiled("^.{{low}}"), 2);
+        }
+
+        #[test]
+        fn from_str() {
+            assert_eq!(
+                "1-3 a: abcde".parse(),
+                Ok(PasswordEntry {
+                    low: 1,
+                    high: 3,
+                    letter: 'a',
+                    password: String::from("abcde")
+                })
+            );
+            let error = |s: &str| s.parse::<PasswordEntry>().unwrap_err();
+            assert_eq!(error("1-3 a abcde").column, 1);
+            assert_eq!(error("1-x a: abcde").column, 3);
+            assert_eq!(error("1-3 ab: abcde").message, "expected a single letter");
+            assert_eq!(error("1-3 a:").column, 7);
+            assert_eq!(error("-1-3 a: abcde").column, 1);
+            assert_eq!(
+                error("1-3 ab: abcde").to_string(),
+                "column 5: expected a single letter"
+            );
+            // backwards numbers are only wrong for some policies
+            assert_eq!("3-1 a: abcde".parse::<PasswordEntry>().unwrap().low, 3);
+            // the line number comes from where the entry is in the input
+            let error = entry(parse::lines("1-3 a: abc\n1-3 a abc").nth(1).unwrap());
+            assert_eq!(
+                error.unwrap_err().to_string(),
+                "line 2, column 1: expected \":\" in \"1-3 a abc\""
+            );
+        }
+
+        #[test]
+        fn generated_passwords() {
+            // one position can't be both held and not held, position 0 never is, and nothing
+            // is between 3 and 1 times
+            let impossible = |spec: &str, wanted: Wanted| match spec {
+                "4-4 q" | "0-0 b" => wanted.position,
+                "3-1 d" => wanted.count,
+                _ => false,
+            };
+            let specs = [
+                "1-3 a", "2-9 c", "1-2 z", "4-4 q", "0-5 b", "0-0 b", "3-1 d", "0-1 e",
+            ];
+            for seed in 0..20 {
+                let mut rng = Rng::new(seed);
+                for spec in &specs {
+                    for &wanted in &Wanted::ALL {
+                        let password = password_for(spec, wanted, &mut rng);
+                        if impossible(spec, wanted) {
+                            assert!(password.is_err(), "{} {:?}", spec, wanted);
+                            continue;
+                        }
+                        let line = format!("{}: {}", spec, password.unwrap());
+                        let entry = line.parse().unwrap();
+                        assert_eq!(CountInRange.is_valid(&entry), wanted.count, "{}", line);
+                        assert_eq!(ExactlyOnePosition.is_valid(&entry), wanted.position);
+                    }
+                }
+            }
+            assert!(password_for("1-3", Wanted::ALL[0], &mut Rng::new(1)).is_err());
+        }
+
+        #[test]
+        fn generated_input() {
+            let mix = [
+                (Wanted::ALL[0], 3),
+                (Wanted::ALL[1], 5),
+                (Wanted::ALL[2], 7),
+            ];
+            let generated = generate_input(&mix, &mut Rng::new(9));
+            assert_eq!(generated.valid_by_count, 8);
+            assert_eq!(generated.valid_by_position, 10);
+            let outcome = solve(&generated.input);
+            assert_eq!(outcome.part1.as_deref(), Some("8"));
+            assert_eq!(outcome.part2.as_deref(), Some("10"));
+            assert_eq!(generate(10, &mut Rng::new(1)).lines().count(), 10);
+        }
+
+        #[test]
+        fn diagnostics() {
+            let check =
+                |spec: &str, line: &str| policy(spec).unwrap().check(&line.parse().unwrap());
+            assert_eq!(
+                check("count", "1-3 a: aaaaab"),
+                Err(String::from("'a' appears 5 times, allowed 1-3"))
+            );
+            assert_eq!(
+                check("position", "1-3 a: bcd"),
+                Err(String::from(
+                    "'a' is in neither: position 1 is 'b', position 3 is 'd'"
+                ))
+            );
+            assert_eq!(
+                check("position", "1-3 a: aba"),
+                Err(String::from("'a' is at both 1 and 3"))
+            );
+            // positions that don't exist are a failed check, not a panic
+            assert_eq!(
+                check("position", "0-9 a: bcd"),
+                Err(String::from(
+                    "'a' is in neither: position 0 doesn't exist (they start at 1), \
+                     position 9 is past the end of the 3-letter password"
+                ))
+            );
+            assert_eq!(check("count", "0-9 a: bcd"), Ok(()));
+            assert_eq!(
+                check("count", "3-1 a: aa"),
+                Err(String::from("3-1 runs backwards, so no count is allowed"))
+            );
+            assert_eq!(check("position", "3-1 a: bca"), Ok(()));
+            assert_eq!(
+                check("at-least:2", "1-2 a: ab"),
+                Err(String::from(
+                    "'a' is at 1 of positions 1 and 2, needs 2: position 2 is 'b'"
+                ))
+            );
+            assert_eq!(
+                check("forbid:xy", "1-2 a: abyx"),
+                Err(String::from("has forbidden 'y' at position 3"))
+            );
+            assert_eq!(
+                check("regex:^{letter}{{low}}", "2-3 a: abc"),
+                Err(String::from("doesn't match ^a{2}"))
+            );
+        }
+    }
 }
 
-// day 1
-fn day1() {
-    let reader = include_str!("1.input");
+mod day3 {
+    use crate::bitboard::BitGrid;
+    use crate::parse;
+    use crate::registry::{self, Outcome};
+    use crate::render;
+    use crate::scale::Rng;
+    use std::collections::HashMap;
+    use std::io;
+    use std::path::Path;
+    use std::str::FromStr;
 
-    let mut nums = Vec::<usize>::new();
-    for line in reader.lines() {
-        nums.push(line.parse().unwrap());
+    // squares moved per step; the map repeats to the right, so any `right` works
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct Slope {
+        pub right: usize,
+        pub down: usize,
     }
 
-    nums.sort();
+    impl Slope {
+        pub fn new(right: usize, down: usize) -> Slope {
+            Slope { right, down }
+        }
+    }
 
-    if let Some(product) = test2(&nums, 2020, 0, nums.len() - 1) {
-        println!("product of two numbers that sum to 2020: {}", product);
+    // "3,1": right 3, down 1
+    impl FromStr for Slope {
+        type Err = String;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let bad = || format!("expected a slope like 3,1 (right, down), not {:?}", s);
+            let (right, down) = s.split_once(',').ok_or_else(bad)?;
+            let right = right.trim().parse().map_err(|_| bad())?;
+            match down.trim().parse() {
+                Ok(0) => Err(format!("slope {:?} never goes down", s)),
+                Ok(down) => Ok(Slope::new(right, down)),
+                Err(_) => Err(bad()),
+            }
+        }
     }
 
-    for j in 1..nums.len() - 2 {
-        let value = nums.remove(j);
-        if let Some(product) = test2(&nums, 2020 - value, 0, nums.len() - 1) {
-            println!(
-                "product of three numbers that sum to 2020 {}",
-                product * value
-            );
-            break;
-        } else {
-            nums.insert(j, value);
+    impl std::fmt::Display for Slope {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "right {}, down {}", self.right, self.down)
         }
     }
-}
 
-fn test2(list: &[usize], desired_sum: usize, i: usize, j: usize) -> Option<usize> {
-    if i == j {
-        return None;
-    } else {
-        let sum = list[i] + list[j];
-        if sum == desired_sum {
-            return Some(list[i] * list[j]);
-        } else if sum > desired_sum {
-            return test2(list, desired_sum, i, j - 1);
-        } else {
-            return test2(list, desired_sum, i + 1, j);
+    pub const SLOPES: [Slope; 5] = [
+        Slope { right: 1, down: 1 },
+        Slope { right: 3, down: 1 },
+        Slope { right: 5, down: 1 },
+        Slope { right: 7, down: 1 },
+        Slope { right: 1, down: 2 },
+    ];
+
+    // where a ride starts, and whether it carries on from the top after the bottom edge
+    #[derive(Copy, Clone, Debug, Default, PartialEq)]
+    pub struct Ride {
+        pub start: (usize, usize), // (row, col) from the top left, 0-based
+        pub wrap_vertically: bool,
+    }
+
+    impl Ride {
+        // "2,5": row 2, column 5
+        pub fn parse_start(s: &str) -> Result<(usize, usize), String> {
+            let bad = || format!("expected a start like 0,0 (row, column), not {:?}", s);
+            let (row, col) = s.split_once(',').ok_or_else(bad)?;
+            match (row.trim().parse(), col.trim().parse()) {
+                (Ok(row), Ok(col)) => Ok((row, col)),
+                _ => Err(bad()),
+            }
         }
     }
-}
 
-// day 2
-fn day2() {
-    let input = include_str!("2.input");
+    // the map, parsed once and checked, and how to ride it. Trees are kept as bits so that
+    // generated maps of millions of rows stay small and quick to ride.
+    pub struct Hill {
+        map: BitGrid,
+        ride: Ride,
+    }
 
-    // "1-2 a: asdf"
-    let mut valid = 0;
-    let mut valid2 = 0;
-    let mut total = 0;
-    for line in input.lines() {
-        let tokens: Vec<&str> = line.split(' ').collect();
-        let range: Vec<&str> = tokens[0].split("-").collect();
-        let letter = tokens[1].chars().nth(0).unwrap();
-        let password = tokens[2];
+    impl Hill {
+        pub fn new(input: &str, ride: Ride) -> Result<Hill, String> {
+            // Rows must all be as wide; CRLF endings and trailing spaces don't count. Each one is
+            // checked and packed as it's read, the map being far too big to hold any other way.
+            // Blank lines may come before or after it but not in the middle, where they'd
+            // quietly move the rows below up.
+            let mut map: Option<BitGrid> = None;
+            let mut gap = None;
+            for line in parse::lines(input) {
+                if line.text.trim().is_empty() {
+                    gap = gap.or(Some(line)).filter(|_| map.is_some());
+                    continue;
+                }
+                if let Some(blank) = gap {
+                    let message = "blank line in the middle of the map";
+                    return Err(blank.error(blank.text, message).to_string());
+                }
+                let text = line.text.trim_end();
+                let width = text.chars().count();
+                let map = map.get_or_insert_with(|| BitGrid::new(width));
+                if width != map.width() {
+                    let message = format!("row is {} wide, expected {}", width, map.width());
+                    return Err(line.error(text, message).to_string());
+                }
+                if let Some((i, c)) = text.char_indices().find(|&(_, c)| c != '#' && c != '.') {
+                    return Err(line
+                        .error(&text[i..], format!("unexpected {:?}", c))
+                        .to_string());
+                }
+                map.push_row(text.chars().map(|c| c == '#'));
+            }
+            let map = match map {
+                Some(map) if map.width() > 0 => map,
+                _ => return Err(String::from("the map is empty")),
+            };
+            let (row, col) = ride.start;
+            if row >= map.height() || col >= map.width() {
+                return Err(format!(
+                    "start {},{} is outside the {}x{} map",
+                    row,
+                    col,
+                    map.height(),
+                    map.width()
+                ));
+            }
+            Ok(Hill { map, ride })
+        }
 
-        let low: usize = range[0].parse().unwrap();
-        let high: usize = range[1].parse().unwrap();
+        fn tree(&self, row: usize, col: usize) -> bool {
+            self.map.get_wrapped(row, col)
+        }
 
-        let count = password.matches(letter).collect::<Vec<&str>>().len();
-        if count >= low && count <= high {
-            valid = valid + 1;
+        // (row, col) of every square a slope lands on, the start included. The map repeats to
+        // the right, so `col` is left unwrapped and the grid wraps it. Wrapping vertically the
+        // ride goes on until it's back on a square it has been on, and both are wrapped.
+        fn path(&self, slope: Slope) -> Vec<(usize, usize)> {
+            let (height, width) = (self.map.height(), self.map.width());
+            let (mut row, mut col) = self.ride.start;
+            let mut path = Vec::new();
+            if self.ride.wrap_vertically {
+                let mut seen = vec![false; height * width];
+                while !seen[row * width + col] {
+                    seen[row * width + col] = true;
+                    path.push((row, col));
+                    row = (row + slope.down) % height;
+                    col = (col + slope.right) % width;
+                }
+            } else {
+                while row < height {
+                    path.push((row, col));
+                    row += slope.down;
+                    col += slope.right;
+                }
+            }
+            path
         }
 
-        let low_char = password.chars().nth(low - 1).unwrap();
-        let high_char = password.chars().nth(high - 1).unwrap();
+        // trees hit on the way down, a tree on the starting square included
+        fn trees(&self, slope: Slope) -> usize {
+            self.tree_counts(&[slope])[0]
+        }
 
-        if low_char == letter {
-            if high_char != letter {
-                valid2 = valid2 + 1;
+        // Trees hit by each slope. Straight down to the bottom they all ride the map together,
+        // row by row; wrapping vertically each follows its own path.
+        fn tree_counts(&self, slopes: &[Slope]) -> Vec<usize> {
+            if self.ride.wrap_vertically {
+                return slopes
+                    .iter()
+                    .map(|&slope| {
+                        self.path(slope)
+                            .into_iter()
+                            .filter(|&(row, col)| self.tree(row, col))
+                            .count()
+                    })
+                    .collect();
             }
-        } else {
-            if high_char == letter {
-                valid2 = valid2 + 1;
+            let steps = slopes
+                .iter()
+                .map(|s| (s.right, s.down))
+                .collect::<Vec<(usize, usize)>>();
+            self.map.count_steps(self.ride.start, &steps)
+        }
+
+        // None if it overflows
+        fn tree_product(&self, slopes: &[Slope]) -> Option<u128> {
+            self.tree_counts(slopes)
+                .into_iter()
+                .try_fold(1u128, |product, trees| product.checked_mul(trees as u128))
+        }
+
+        // Every slope from right 1 to the map's width (where it's back to straight down) and
+        // down 1 to its height, with its tree count, fewest trees first.
+        pub fn all_slopes(&self) -> Vec<(Slope, usize)> {
+            let width = self.map.width();
+            let slopes = (1..self.map.height().max(2))
+                .flat_map(|down| (1..=width).map(move |right| Slope::new(right, down)))
+                .collect::<Vec<Slope>>();
+            let mut counts = slopes
+                .iter()
+                .copied()
+                .zip(self.tree_counts(&slopes))
+                .collect::<Vec<(Slope, usize)>>();
+            counts.sort_by_key(|&(s, trees)| (trees, s.down, s.right));
+            counts
+        }
+    }
+
+    pub fn day3(input: &str, slopes: &[Slope], ride: Ride, optimise: Option<usize>) {
+        let hill = match Hill::new(input, ride) {
+            Ok(hill) => hill,
+            Err(e) => {
+                println!("day 3 input: {}", e);
+                return;
+            }
+        };
+        match optimise {
+            Some(k) => print_optimised(&hill, k),
+            None => {
+                for &slope in slopes {
+                    println!("{}: {} trees", slope, hill.trees(slope));
+                }
+                let product = hill
+                    .tree_product(slopes)
+                    .map_or(String::from("(overflows u128)"), |p| p.to_string());
+                println!("Product of tree counts: {}", product);
             }
         }
-        total = total + 1;
     }
 
-    println!(
-        "{} of {} passwords are valid according to policy 1",
-        valid, total
-    );
-    println!(
-        "{} of {} passwords are valid according to policy 2",
-        valid2, total
-    );
-}
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
+
+    fn part1(hill: &Hill) -> String {
+        hill.trees(Slope::new(3, 1)).to_string()
+    }
 
-// day 3
-fn day3() {
-    let input = include_str!("3.input");
+    fn part2(hill: &Hill) -> String {
+        hill.tree_product(&SLOPES)
+            .map_or(String::from("none"), |p| p.to_string())
+    }
 
-    #[derive(Copy, Clone, Debug)]
-    struct Cursor {
-        pos: usize,
-        right: usize,
-        down: usize,
-        trees: usize,
-    };
-    impl Cursor {
-        fn new(r: usize, d: usize) -> Cursor {
-            return Cursor {
-                pos: 0,
-                right: r,
-                down: d,
-                trees: 0,
-            };
+    // the puzzle's ride: from the top left to the bottom
+    fn parse(input: &str) -> Hill {
+        Hill::new(input, Ride::default()).unwrap_or_else(|e| panic!("day 3 input: {}", e))
+    }
+
+    // for `scale`: `size` rows as wide as the puzzle's, about one square in five a tree
+    pub fn generate(size: usize, rng: &mut Rng) -> String {
+        let mut out = String::with_capacity(size * 32);
+        for _ in 0..size {
+            for _ in 0..31 {
+                out.push(if rng.chance(20) { '#' } else { '.' });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn print_optimised(hill: &Hill, k: usize) {
+        let counts = hill.all_slopes();
+        let (fewest, most) = match (counts.first(), counts.last()) {
+            (Some(&(_, fewest)), Some(&(_, most))) => (fewest, most),
+            _ => {
+                println!("The map is empty");
+                return;
+            }
+        };
+        println!(
+            "{} slopes, right 1-{} and down 1-{}",
+            counts.len(),
+            hill.map.width(),
+            hill.map.height().max(2) - 1
+        );
+        let with = |n: usize| {
+            let slopes = counts.iter().filter(|c| c.1 == n).collect::<Vec<_>>();
+            let shown = slopes
+                .iter()
+                .take(5)
+                .map(|(s, _)| format!("({})", s))
+                .collect::<Vec<String>>()
+                .join(" ");
+            let more = slopes.len().saturating_sub(5);
+            if more > 0 {
+                format!("{} and {} more", shown, more)
+            } else {
+                shown
+            }
+        };
+        println!("fewest trees: {} with {}", fewest, with(fewest));
+        println!("most trees: {} with {}", most, with(most));
+
+        if k > counts.len() {
+            println!("There aren't {} different slopes to multiply", k);
+            return;
+        }
+        // tree counts are never negative, so the extreme products come from the extreme counts
+        let product = |chosen: &[(Slope, usize)]| {
+            let value = chosen
+                .iter()
+                .try_fold(1u128, |p, &(_, t)| p.checked_mul(t as u128))
+                .map_or(String::from("(overflows u128)"), |p| p.to_string());
+            let slopes = chosen
+                .iter()
+                .map(|(s, t)| format!("({}: {})", s, t))
+                .collect::<Vec<String>>();
+            format!("{} = {}", value, slopes.join(" x "))
+        };
+        println!("best product of {} slopes: {}", k, product(&counts[..k]));
+        println!(
+            "worst product of {} slopes: {}",
+            k,
+            product(&counts[counts.len() - k..])
+        );
+    }
+
+    // The map repeated to the right as far as the slopes reach, with the squares each slope
+    // lands on. Where slopes cross, the later one wins.
+    struct TobogganMap<'a> {
+        hill: &'a Hill,
+        width: usize,                           // of all the copies together
+        visits: HashMap<(usize, usize), usize>, // square -> index of the slope
+    }
+
+    impl<'a> TobogganMap<'a> {
+        fn new(hill: &'a Hill, slopes: &[Slope]) -> Self {
+            let mut visits = HashMap::new();
+            for (i, &slope) in slopes.iter().enumerate() {
+                visits.extend(hill.path(slope).into_iter().map(|square| (square, i)));
+            }
+            let width = hill.map.width();
+            let reach = visits.keys().map(|&(_, col)| col + 1).max().unwrap_or(0);
+            TobogganMap {
+                hill,
+                width: width * reach.div_ceil(width).max(1),
+                visits,
+            }
         }
-        fn shift(&mut self, width: usize) {
-            self.pos = (self.pos + self.right) % (width + 1);
+
+        fn tree(&self, row: usize, col: usize) -> bool {
+            self.hill.tree(row, col)
         }
-        fn tree(&mut self) {
-            self.trees = self.trees + 1;
+
+        // The puzzle's picture: `O` where a slope lands on open snow and `X` where it hits a
+        // tree, or each slope's own pair of marks. With `color` the marks are also coloured
+        // like the slope's path in the images.
+        fn draw(&self, marks: &[(char, char)], color: bool) -> String {
+            let mut out = String::new();
+            for row in 0..self.hill.map.height() {
+                for col in 0..self.width {
+                    let tree = self.tree(row, col);
+                    match self.visits.get(&(row, col)) {
+                        Some(&i) => {
+                            let (open, hit) = match marks {
+                                [] => ('O', 'X'),
+                                _ => marks[i % marks.len()],
+                            };
+                            let mark = if tree { hit } else { open };
+                            if color {
+                                let (r, g, b) = render::PALETTE[i % render::PALETTE.len()];
+                                out += &format!("\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, mark);
+                            } else {
+                                out.push(mark);
+                            }
+                        }
+                        None => out.push(if tree { '#' } else { '.' }),
+                    }
+                }
+                out.push('\n');
+            }
+            out
         }
     }
-    let mut cursors = vec![
-        Cursor::new(1, 1),
-        Cursor::new(3, 1),
-        Cursor::new(5, 1),
-        Cursor::new(7, 1),
-        Cursor::new(1, 2),
-    ];
 
-    let mut width = 0;
-    for (index, line) in input.lines().enumerate() {
-        if index == 0 {
-            width = line.len() - 1;
-            continue;
-        }
-        for cursor in &mut cursors {
-            if (index % cursor.down) == 0 {
-                cursor.shift(width);
-                if line.chars().nth(cursor.pos).unwrap() == '#' {
-                    cursor.tree();
+    impl render::GridRender for TobogganMap<'_> {
+        fn dimensions(&self) -> (usize, usize) {
+            (self.hill.map.height(), self.width)
+        }
+
+        // trees on a path are drawn darker
+        fn cell_color(&self, row: usize, col: usize) -> render::Rgb {
+            match (self.visits.get(&(row, col)), self.tree(row, col)) {
+                (Some(i), false) => render::PALETTE[i % render::PALETTE.len()],
+                (Some(i), true) => {
+                    let (r, g, b) = render::PALETTE[i % render::PALETTE.len()];
+                    (r / 2, g / 2, b / 2)
                 }
+                (None, true) => (0x22, 0x8b, 0x22),
+                (None, false) => render::WHITE,
+            }
+        }
+    }
+
+    // "OX,ox": the open and tree marks for each slope in turn
+    pub fn parse_marks(spec: &str) -> Result<Vec<(char, char)>, String> {
+        spec.split(',')
+            .map(|pair| match pair.chars().collect::<Vec<char>>()[..] {
+                [open, hit] => Ok((open, hit)),
+                _ => Err(format!(
+                    "expected two marks per slope, open then tree (like OX), not {:?}",
+                    pair
+                )),
+            })
+            .collect()
+    }
+
+    fn invalid(e: String) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+
+    // the map with the slopes drawn on, to stdout or a file
+    pub fn draw(
+        input: &str,
+        slopes: &[Slope],
+        ride: Ride,
+        marks: &[(char, char)],
+        color: bool,
+        file: Option<&Path>,
+    ) -> io::Result<()> {
+        let hill = Hill::new(input, ride).map_err(invalid)?;
+        let drawing = TobogganMap::new(&hill, slopes).draw(marks, color);
+        match file {
+            Some(file) => std::fs::write(file, drawing),
+            None => {
+                print!("{}", drawing);
+                Ok(())
             }
         }
     }
-    let mut trees = 1;
-    for cursor in cursors {
-        trees = trees * cursor.trees;
+
+    pub fn export(
+        input: &str,
+        slopes: &[Slope],
+        ride: Ride,
+        dir: &Path,
+        format: render::ImageFormat,
+        scale: usize,
+    ) -> io::Result<()> {
+        let hill = Hill::new(input, ride).map_err(invalid)?;
+        std::fs::create_dir_all(dir)?;
+        render::write_image(
+            &TobogganMap::new(&hill, slopes),
+            &dir.join(format!("day3.{}", format.extension())),
+            format,
+            scale,
+        )?;
+        for &slope in slopes {
+            render::write_image(
+                &TobogganMap::new(&hill, &[slope]),
+                &dir.join(format!(
+                    "day3_r{}_d{}.{}",
+                    slope.right,
+                    slope.down,
+                    format.extension()
+                )),
+                format,
+                scale,
+            )?;
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const INPUT: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+        #[test]
+        fn example() {
+            let hill = parse(INPUT);
+            let counts = SLOPES.iter().map(|&s| hill.trees(s)).collect::<Vec<_>>();
+            assert_eq!(counts, [2, 7, 3, 4, 2]);
+            assert_eq!(hill.tree_product(&SLOPES), Some(336));
+            assert_eq!(hill.path(Slope::new(1, 2))[..3], [(0, 0), (2, 1), (4, 2)]);
+        }
+
+        #[test]
+        fn maps() {
+            // CRLF endings and trailing whitespace parse to the same map
+            let crlf = INPUT.replace('\n', "  \r\n");
+            assert_eq!(parse(&crlf).map, parse(INPUT).map);
+
+            let error = |input: &str, ride| Hill::new(input, ride).err().unwrap();
+            assert_eq!(
+                error("..#\n.#\n", Ride::default()),
+                "line 2, column 1: row is 2 wide, expected 3"
+            );
+            assert_eq!(
+                error("..#\n.o.\n", Ride::default()),
+                "line 2, column 2: unexpected 'o'"
+            );
+            assert_eq!(error("\n", Ride::default()), "the map is empty");
+            assert_eq!(
+                error("..#\n#..\n\n \n.#.\n", Ride::default()),
+                "line 3, column 1: blank line in the middle of the map"
+            );
+            assert_eq!(parse("\n\n#.\n.#\n\n\n").map, parse("#.\n.#\n").map);
+            let outside = Ride {
+                start: (0, 3),
+                wrap_vertically: false,
+            };
+            assert_eq!(error("..#\n", outside), "start 0,3 is outside the 1x3 map");
+            assert_eq!(Ride::parse_start(" 2, 5"), Ok((2, 5)));
+            assert!(Ride::parse_start("2").is_err());
+
+            // a tree on the first row, where the ride starts, is hit too
+            assert_eq!(parse("#.\n.#\n").trees(Slope::new(1, 1)), 2);
+        }
+
+        #[test]
+        fn rides() {
+            let from = |start, wrap_vertically| {
+                let ride = Ride {
+                    start,
+                    wrap_vertically,
+                };
+                Hill::new(INPUT, ride).unwrap()
+            };
+            // from row 1, column 2 the 3,1 slope lands on 10 squares
+            let hill = from((1, 2), false);
+            assert_eq!(hill.path(Slope::new(3, 1))[..3], [(1, 2), (2, 5), (3, 8)]);
+            assert_eq!(hill.path(Slope::new(3, 1)).len(), 10);
+
+            // wrapping both ways on an 11x11 map, 3,1 visits 11 squares before repeating
+            let hill = from((0, 0), true);
+            let path = hill.path(Slope::new(3, 1));
+            assert_eq!(path.len(), 11);
+            assert_eq!(path[10], (10, 8));
+            assert_eq!(hill.trees(Slope::new(3, 1)), 7);
+            // 1,2 on 11 rows: rows 0, 2, .., 10, 1, 3, .., 9
+            let path = hill.path(Slope::new(1, 2));
+            assert_eq!(path.len(), 11);
+            assert_eq!(path[6], (1, 6));
+            let drawing = TobogganMap::new(&hill, &[Slope::new(1, 2)]).draw(&[], false);
+            assert_eq!(drawing.lines().next().unwrap().len(), 11);
+        }
+
+        #[test]
+        fn slopes() {
+            assert_eq!("3,1".parse(), Ok(Slope::new(3, 1)));
+            assert_eq!(" 40 , 7".parse(), Ok(Slope::new(40, 7)));
+            assert!("3,0".parse::<Slope>().is_err());
+            assert!("3".parse::<Slope>().is_err());
+            assert!("-1,2".parse::<Slope>().is_err());
+
+            let all = parse(INPUT).all_slopes();
+            assert_eq!(all.len(), 11 * 10);
+            assert!(all.windows(2).all(|w| w[0].1 <= w[1].1));
+            // going right by the width is going straight down: 3 trees in the first column
+            let down = all.iter().find(|(s, _)| *s == Slope::new(11, 1)).unwrap();
+            assert_eq!(down.1, 3);
+            assert_eq!(
+                all.last().unwrap().1,
+                all.iter().map(|c| c.1).max().unwrap()
+            );
+        }
+
+        #[test]
+        fn drawing() {
+            let hill = parse(INPUT);
+            let drawing = TobogganMap::new(&hill, &[Slope::new(3, 1)]).draw(&[], false);
+            let lines = drawing.lines().collect::<Vec<&str>>();
+            // the puzzle's picture, cut down to the first two copies of the map
+            assert_eq!(&lines[0][..22], "O.##.........##.......");
+            assert_eq!(&lines[1][..22], "#..O#...#..#...#...#..");
+            assert_eq!(&lines[2][..22], ".#....X..#..#....#..#.");
+            assert_eq!(&lines[10][..33], ".#..#...#.#.#..#...#.#.#..#...X.#");
+            // wide enough for the last square, in whole copies of the map
+            assert_eq!(lines[0].len(), 33);
+
+            let two = TobogganMap::new(&hill, &[Slope::new(1, 1), Slope::new(1, 2)]);
+            let marks = parse_marks("OX,ox").unwrap();
+            let drawing = two.draw(&marks, false);
+            assert!(drawing.starts_with("o.##"));
+            assert_eq!(drawing.lines().nth(1).unwrap().chars().nth(1), Some('O'));
+            assert!(two.draw(&marks, true).contains("\x1b[38;2;"));
+            assert!(parse_marks("OX,o").is_err());
+        }
     }
-    println!("Found {} trees", trees);
 }
 
-// day 4
-use std::collections::HashMap;
-fn day4() {
+mod day4 {
+    use crate::parse;
+    use crate::registry::{self, Outcome};
     use regex::Regex;
-
-    let input = include_str!("4.input");
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::Path;
+    use std::sync::{Arc, RwLock};
 
     /*
     // don't care about values, but probably will later
@@ -212,232 +2248,502 @@ fn day4() {
     }
     */
 
-    fn is_valid(kvs: &HashMap<&str, &str>) -> bool {
-        lazy_static! { static ref REQUIRED_KEYS: Vec<&'static str> = vec!["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", /*"cid"*/]; }
-        for required_key in REQUIRED_KEYS.iter() {
-            if !kvs.contains_key(required_key) {
-                return false;
+    // The puzzle's rules, as a schema; see the file for the format. Other rules come from
+    // `--schema FILE`, or a file of this name in the input directory.
+    pub const DEFAULT_SCHEMA: &str = include_str!("../schemas/passports.schema");
+    pub const SCHEMA_NAME: &str = "passports.schema";
+
+    lazy_static! {
+        // what `solve` checks against: the puzzle's rules unless `use_schema` swapped others in
+        static ref RULES: RwLock<Arc<Schema>> = RwLock::new(Arc::new(Schema::default()));
+    }
+
+    // what a field's value has to look like
+    enum Rule {
+        Any,
+        Range(u64, u64),
+        Units(Vec<(String, u64, u64)>),
+        Pattern(Regex),
+        OneOf(Vec<String>),
+    }
+
+    impl Rule {
+        fn parse<'a>(line: &parse::Line<'a>, text: &'a str) -> parse::Result<Rule> {
+            let range = |part: &'a str| -> parse::Result<(u64, u64)> {
+                let (low, high) = line.split_once(part.trim(), "-")?;
+                Ok((line.parse_at(low)?, line.parse_at(high)?))
+            };
+            let (name, args) = text.split_once(' ').unwrap_or((text, ""));
+            let args = args.trim();
+            match name {
+                "any" if args.is_empty() => Ok(Rule::Any),
+                "range" => range(args).map(|(low, high)| Rule::Range(low, high)),
+                "units" => args
+                    .split(',')
+                    .map(|unit| {
+                        let (unit, limits) = line.split_once(unit.trim(), " ")?;
+                        let (low, high) = range(limits)?;
+                        Ok((String::from(unit), low, high))
+                    })
+                    .collect::<parse::Result<Vec<_>>>()
+                    .map(Rule::Units),
+                "pattern" => Regex::new(&format!("^(?:{})$", args))
+                    .map(Rule::Pattern)
+                    .map_err(|e| line.error(args, format!("bad pattern: {}", e))),
+                "one-of" if !args.is_empty() => Ok(Rule::OneOf(
+                    args.split_whitespace().map(String::from).collect(),
+                )),
+                _ => Err(line.error(text, format!("unknown rule {:?}", text))),
             }
         }
 
-        if !kvs["byr"]
-            .parse::<usize>()
-            .map(|v| v >= 1920 && v <= 2002)
-            .unwrap()
-        {
-            return false;
+        fn check(&self, value: &str) -> bool {
+            let number = |n: &str| match n.bytes().all(|b| b.is_ascii_digit()) {
+                true => n.parse::<u64>().ok(),
+                false => None,
+            };
+            let within = |n, low, high| number(n).is_some_and(|n| low <= n && n <= high);
+            match self {
+                Rule::Any => true,
+                Rule::Range(low, high) => within(value, *low, *high),
+                Rule::Units(units) => units.iter().any(|(unit, low, high)| {
+                    value
+                        .strip_suffix(unit.as_str())
+                        .is_some_and(|n| within(n, *low, *high))
+                }),
+                Rule::Pattern(re) => re.is_match(value),
+                Rule::OneOf(words) => words.iter().any(|w| w == value),
+            }
         }
-        if !kvs["iyr"]
-            .parse::<usize>()
-            .map(|v| v >= 2010 && v <= 2020)
-            .unwrap()
-        {
-            return false;
+    }
+
+    struct Field {
+        name: String,
+        required: bool,
+        rule: Rule,
+    }
+
+    pub struct Schema {
+        text: String,
+        fields: Vec<Field>,
+    }
+
+    impl Schema {
+        pub fn parse(text: &str) -> parse::Result<Schema> {
+            let mut fields: Vec<Field> = Vec::new();
+            for line in parse::lines(text) {
+                let trimmed = line.text.trim();
+                if trimmed.is_empty() || trimmed.starts_with('#') {
+                    continue;
+                }
+                let (name, rule) = line.key_value(":")?;
+                if fields.iter().any(|f| f.name == name) {
+                    return Err(line.error(name, format!("{} has two rules", name)));
+                }
+                let (required, rule) = match rule.strip_prefix("optional") {
+                    Some(rest) => (false, rest.trim_start()),
+                    None => (true, rule),
+                };
+                fields.push(Field {
+                    name: String::from(name),
+                    required,
+                    rule: Rule::parse(&line, rule)?,
+                });
+            }
+            Ok(Schema {
+                text: String::from(text),
+                fields,
+            })
         }
-        if !kvs["eyr"]
-            .parse::<usize>()
-            .map(|v| v >= 2020 && v <= 2030)
-            .unwrap()
-        {
-            return false;
+
+        pub fn read(file: &Path) -> Result<Schema, String> {
+            let text =
+                fs::read_to_string(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+            Schema::parse(&text).map_err(|e| format!("{}: {}", file.display(), e))
         }
 
-        lazy_static! {
-            static ref RE_HGT: Regex = Regex::new(r"^(\d{2,3})(cm|in)$").unwrap();
+        fn has_required_keys(&self, kvs: &HashMap<&str, &str>) -> bool {
+            self.fields
+                .iter()
+                .all(|f| !f.required || kvs.contains_key(f.name.as_str()))
         }
-        if !match RE_HGT.captures(&kvs["hgt"]) {
-            Some(caps) => match caps.get(1).unwrap().as_str().parse::<usize>() {
-                Ok(hgt) => match caps.get(2) {
-                    Some(unit) if unit.as_str() == "cm" => hgt >= 150 && hgt <= 193,
-                    Some(unit) if unit.as_str() == "in" => hgt >= 59 && hgt <= 76,
-                    _ => false,
-                },
-                Err(_) => false,
-            },
-            None => false,
-        } {
-            return false;
+
+        fn is_valid(&self, kvs: &HashMap<&str, &str>) -> bool {
+            self.fields.iter().all(|f| match kvs.get(f.name.as_str()) {
+                Some(value) => f.rule.check(value),
+                None => !f.required,
+            })
         }
+    }
 
-        lazy_static! {
-            static ref RE_HCL: Regex = Regex::new(r"^#[a-f0-9]{6}$").unwrap();
+    impl Default for Schema {
+        fn default() -> Self {
+            Schema::parse(DEFAULT_SCHEMA)
+                .unwrap_or_else(|e| panic!("built-in passport schema: {}", e))
         }
-        if !RE_HCL.is_match(&kvs["hcl"]) {
-            return false;
+    }
+
+    pub fn use_schema(schema: Schema) {
+        *RULES.write().unwrap() = Arc::new(schema);
+    }
+
+    fn rules() -> Arc<Schema> {
+        Arc::clone(&RULES.read().unwrap())
+    }
+
+    pub fn day4(input: &str) {
+        let passports = parse(input);
+        let rules = rules();
+        let valid = passports.iter().filter(|kvs| rules.is_valid(kvs)).count();
+        println!("Found {} valid passports", valid);
+    }
+
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
+
+    // the rules `solve` checks, as written
+    pub fn rules_text() -> String {
+        rules().text.clone()
+    }
+
+    fn part1(passports: &[HashMap<&str, &str>]) -> String {
+        let rules = rules();
+        passports
+            .iter()
+            .filter(|kvs| rules.has_required_keys(kvs))
+            .count()
+            .to_string()
+    }
+
+    fn part2(passports: &[HashMap<&str, &str>]) -> String {
+        let rules = rules();
+        passports
+            .iter()
+            .filter(|kvs| rules.is_valid(kvs))
+            .count()
+            .to_string()
+    }
+
+    fn parse(input: &str) -> Vec<HashMap<&str, &str>> {
+        parse::records(input)
+            .iter()
+            .map(|record| {
+                let mut kvs: HashMap<&str, &str> = HashMap::new();
+                for line in record {
+                    let pairs = line
+                        .pairs(":")
+                        .unwrap_or_else(|e| panic!("day 4 input: {}", e));
+                    kvs.extend(pairs);
+                }
+                kvs
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const INPUT: &str = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in
+";
+
+        #[test]
+        fn example() {
+            let passports = parse(INPUT);
+            assert_eq!(part1(&passports), "2");
+            assert_eq!(part2(&passports), "2");
         }
 
-        lazy_static! {
-            static ref VALID_ECL: Vec<&'static str> =
-                vec!["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+        #[test]
+        fn rules() {
+            let puzzle = Schema::default();
+            let check = |field: &str, value| {
+                let passport = [("byr", "2002"), ("iyr", "2010"), ("eyr", "2030")];
+                let mut kvs = passport.iter().copied().collect::<HashMap<&str, &str>>();
+                kvs.extend([
+                    ("hgt", "60in"),
+                    ("hcl", "#123abc"),
+                    ("ecl", "brn"),
+                    ("pid", "000000001"),
+                ]);
+                kvs.insert(field, value);
+                puzzle.is_valid(&kvs)
+            };
+            // the puzzle's examples
+            assert!(check("byr", "2002") && !check("byr", "2003"));
+            assert!(check("hgt", "60in") && check("hgt", "190cm"));
+            assert!(!check("hgt", "190in") && !check("hgt", "190"));
+            assert!(check("hcl", "#123abc") && !check("hcl", "#123abz") && !check("hcl", "123abc"));
+            assert!(check("ecl", "brn") && !check("ecl", "wat"));
+            assert!(check("pid", "000000001") && !check("pid", "0123456789"));
+            assert!(!check("byr", "+2000") && check("cid", "anything"));
         }
-        if !VALID_ECL.contains(&kvs["ecl"]) {
-            return false;
+
+        #[test]
+        fn custom_schema() {
+            let schema =
+                Schema::parse("# just two\nbyr: range 1-5\nhgt: optional units m 1-2\n").unwrap();
+            let passport = |text| parse(text).remove(0);
+            assert!(schema.is_valid(&passport("byr:3 ecl:anything")));
+            assert!(schema.is_valid(&passport("byr:5 hgt:2m")));
+            assert!(!schema.is_valid(&passport("byr:5 hgt:2cm")));
+            assert!(!schema.is_valid(&passport("hgt:2m")));
+
+            let error = |text| Schema::parse(text).err().unwrap().to_string();
+            assert_eq!(
+                error("byr range 1-5"),
+                "line 1, column 1: expected \":\" in \"byr range 1-5\""
+            );
+            assert_eq!(
+                error("byr: range 1"),
+                "line 1, column 12: expected \"-\" in \"1\""
+            );
+            assert_eq!(
+                error("a: any\nb: sometimes"),
+                "line 2, column 4: unknown rule \"sometimes\""
+            );
+            assert_eq!(error("a: any\na: any"), "line 2, column 1: a has two rules");
+            assert!(error("a: pattern (").starts_with("line 1, column 12: bad pattern"));
         }
 
-        lazy_static! {
-            static ref RE_PID: Regex = Regex::new(r"^\d{9}$").unwrap();
+        #[test]
+        fn schema_file() {
+            let file = std::env::temp_dir().join(format!("aoc-schema-test-{}", std::process::id()));
+            let passport = |text| parse(text).remove(0);
+
+            // a file that's missing or isn't a schema is an error, not the built-in rules
+            assert!(Schema::read(&file)
+                .err()
+                .unwrap()
+                .contains("aoc-schema-test"));
+            fs::write(&file, "byr range\n").unwrap();
+            assert!(Schema::read(&file)
+                .err()
+                .unwrap()
+                .contains("line 1, column 1"));
+            fs::write(&file, "byr: range 0-9\n").unwrap();
+            let schema = Schema::read(&file).unwrap();
+            assert!(schema.is_valid(&passport("byr:1")));
+            assert_eq!(schema.text, "byr: range 0-9\n");
+            fs::remove_file(&file).unwrap();
         }
-        if !RE_PID.is_match(&kvs["pid"]) {
-            return false;
+    }
+}
+
+mod day5 {
+    use crate::registry::{self, Outcome};
+    use num_traits::pow;
+
+    fn row(pass: &str) -> usize {
+        let mut row = 0;
+        for (index, ch) in pass.chars().take(7).enumerate() {
+            if ch == 'B' {
+                row = row + pow(2, 7 - index - 1);
+            }
+        }
+        return row;
+    }
+
+    fn col(pass: &str) -> usize {
+        let mut col = 0;
+        for (index, ch) in pass.chars().skip(7).enumerate() {
+            if ch == 'R' {
+                col = col + pow(2, 3 - index - 1);
+            }
         }
+        return col;
+    }
+
+    // closed form solution for sum(i..k)
+    fn cumsum(k: usize) -> usize {
+        k * (k + 1) / 2
+    }
+
+    pub fn day5(input: &str) {
+        let seat_ids = parse(input);
+
+        println!(
+            "The maximum seat id found in the input is {}",
+            max_seat_id(&seat_ids)
+        );
+        println!("My seat id is {}", my_seat_id(&seat_ids));
+    }
+
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
 
-        return true;
+    fn part1(seat_ids: &[usize]) -> String {
+        max_seat_id(seat_ids).to_string()
     }
 
-    let mut valid = 0;
-    let mut kvs: HashMap<&str, &str> = HashMap::new();
-    for line in input.lines() {
-        if line == "" {
-            if is_valid(&kvs) {
-                valid = valid + 1;
-            }
-            kvs.clear();
-        }
-        for pair in line.split(' ') {
-            let row_pairs = pair.split(':').collect::<Vec<&str>>();
-            if let [key, value] = &row_pairs[..] {
-                kvs.insert(key, value);
-            }
-        }
+    fn part2(seat_ids: &[usize]) -> String {
+        my_seat_id(seat_ids).to_string()
     }
-    if is_valid(&kvs) {
-        valid = valid + 1;
+
+    fn parse(input: &str) -> Vec<usize> {
+        input
+            .lines()
+            .map(|line| row(line) * 8 + col(line))
+            .collect()
     }
-    println!("Found {} valid passports", valid);
-}
 
-// day 5
+    fn max_seat_id(seat_ids: &[usize]) -> usize {
+        *seat_ids.iter().max().unwrap()
+    }
 
-fn row(pass: &str) -> usize {
-    let mut row = 0;
-    for (index, ch) in pass.chars().take(7).enumerate() {
-        if ch == 'B' {
-            row = row + pow(2, 7 - index - 1);
-        }
+    fn my_seat_id(seat_ids: &[usize]) -> usize {
+        let max_id = max_seat_id(seat_ids);
+        let min_id = *seat_ids.iter().min().unwrap();
+        let sum_id: usize = seat_ids.iter().sum();
+
+        let expected_sum = cumsum(max_id) - cumsum(min_id - 1);
+        expected_sum - sum_id
     }
-    return row;
-}
 
-fn col(pass: &str) -> usize {
-    let mut col = 0;
-    for (index, ch) in pass.chars().skip(7).enumerate() {
-        if ch == 'R' {
-            col = col + pow(2, 3 - index - 1);
-        }
+    #[test]
+    fn test_row() {
+        assert_eq!(row("BFFFBBFRRR"), 70);
+        assert_eq!(row("FFFBBBFRRR"), 14);
+        assert_eq!(row("BBFFBBFRLL"), 102);
     }
-    return col;
-}
 
-// closed form solution for sum(i..k)
-fn cumsum(k: usize) -> usize {
-    k * (k + 1) / 2
-}
+    #[test]
+    fn test_col() {
+        assert_eq!(col("BFFFBBFRRR"), 7);
+        assert_eq!(col("FFFBBBFRRR"), 7);
+        assert_eq!(col("BBFFBBFRLL"), 4);
+    }
 
-fn day5() {
-    let input = include_str!("5.input");
-    let mut max_id = 0;
-    let mut min_id = 1000;
-    let mut sum_id = 0;
-    for line in input.lines() {
-        let seat_id = row(line) * 8 + col(line);
-        if seat_id > max_id {
-            max_id = seat_id;
-        }
-        if seat_id < min_id {
-            min_id = seat_id;
-        }
-        sum_id = sum_id + seat_id;
+    #[test]
+    fn test_cumsum() {
+        assert_eq!(cumsum(3), 6);
+        assert_eq!(cumsum(4), 10);
     }
+}
 
-    let expected_sum = cumsum(max_id) - cumsum(min_id - 1);
-    let my_seat_id = expected_sum - sum_id;
+mod day6 {
+    use crate::parse;
+    use crate::registry::{self, Outcome};
+    use std::collections::HashSet;
 
-    println!("The maximum seat id found in the input is {}", max_id);
-    println!("My seat id is {}", my_seat_id);
-}
+    pub fn day6(input: &str) {
+        let groups = parse(input);
+        println!(
+            "The sum of common questions per group is {}",
+            common_question_sum(&groups)
+        );
+    }
 
-#[test]
-fn test_row() {
-    assert_eq!(row("BFFFBBFRRR"), 70);
-    assert_eq!(row("FFFBBBFRRR"), 14);
-    assert_eq!(row("BBFFBBFRLL"), 102);
-}
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
 
-#[test]
-fn test_col() {
-    assert_eq!(col("BFFFBBFRRR"), 7);
-    assert_eq!(col("FFFBBBFRRR"), 7);
-    assert_eq!(col("BBFFBBFRLL"), 4);
-}
+    fn part1(groups: &[Vec<&str>]) -> String {
+        groups
+            .iter()
+            .map(|group| group.concat().chars().collect::<HashSet<char>>().len())
+            .sum::<usize>()
+            .to_string()
+    }
 
-#[test]
-fn test_cumsum() {
-    assert_eq!(cumsum(3), 6);
-    assert_eq!(cumsum(4), 10);
-}
+    fn part2(groups: &[Vec<&str>]) -> String {
+        common_question_sum(groups).to_string()
+    }
 
-use std::collections::HashSet;
-// day 6
-fn day6() {
-    let input = include_str!("6.input");
+    fn parse(input: &str) -> Vec<Vec<&str>> {
+        parse::records(input)
+            .iter()
+            .map(|group| group.iter().map(|l| l.text).collect())
+            .collect()
+    }
 
-    let mut mark = true;
-    let mut sum = 0;
-    let mut group_qs: HashSet<char> = HashSet::new();
-    for line in input.lines() {
-        if line == "" {
-            sum = sum + group_qs.len();
-            group_qs.clear();
-            mark = true;
-        } else {
-            if mark {
-                group_qs = line.chars().collect::<HashSet<char>>();
-                mark = false;
-            } else {
-                let person_qs: HashSet<char> = line.chars().collect();
-                group_qs = group_qs.intersection(&person_qs).cloned().collect();
+    fn common_question_sum(groups: &[Vec<&str>]) -> usize {
+        let mut sum = 0;
+        for group in groups {
+            let mut mark = true;
+            let mut group_qs: HashSet<char> = HashSet::new();
+            for line in group {
+                if mark {
+                    group_qs = line.chars().collect::<HashSet<char>>();
+                    mark = false;
+                } else {
+                    let person_qs: HashSet<char> = line.chars().collect();
+                    group_qs = group_qs.intersection(&person_qs).cloned().collect();
+                }
             }
+            sum = sum + group_qs.len();
         }
+        sum
     }
-    sum = sum + group_qs.len();
-
-    println!("The sum of common questions per group is {}", sum);
 }
 
 // day 7
 
 // dynamic programming alarm bells going off!
+mod day7 {
+    use crate::parse;
+    use crate::registry::{self, Outcome};
+    use multimap::MultiMap;
+    use std::collections::{HashMap, HashSet};
+
+    const MY_COLOR: &str = "shiny gold";
+
+    // key `is contained in` value relationships, and key `contains` value relationships
+    type Bags<'a> = (
+        MultiMap<&'a str, &'a str>,
+        HashMap<&'a str, HashMap<&'a str, usize>>,
+    );
 
-use multimap::MultiMap;
-//use std::collections::HashMap;
+    pub fn day7(input: &str) {
+        let (is_contained_in, contains) = parse(input);
 
-fn day7() {
-    let input = include_str!("7.input");
+        let mut super_colors: HashSet<&str> = HashSet::new();
+        find_super_colors(MY_COLOR, &is_contained_in, &mut super_colors);
+        println!(
+            "A total of {} colors of bags may indirectly contain a {} bag",
+            super_colors.len(),
+            MY_COLOR
+        );
+        let inner_bag_count = find_total_bag_count(MY_COLOR, &contains) - 1; // minus my_color bag
+        println!("A {} bag contains {} inner bags", MY_COLOR, inner_bag_count);
+    }
 
-    // store key `is contained in` value relationships
-    let mut is_contained_in: MultiMap<&str, &str> = MultiMap::new();
-    let mut contains: HashMap<&str, HashMap<&str, usize>> = HashMap::new();
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
 
-    // "muted lime bags contain 1 wavy lime bag, 1 vibrant green bag, 3 light yellow bags."
-    // "dotted teal bags contain no other bags."
-    for line in input.lines() {
-        let parts: Vec<&str> = line.split(" bags contain ").collect();
-        let subject = parts[0];
-        let objects = parts[1];
-        if objects == "no other bags." {
-            continue;
-        } else {
-            let objects = parts[1].split(", ");
-            for object in objects {
-                let object_parts = object
-                    .trim_end_matches(|c| c == '.' || c == ',')
-                    .trim_end_matches("bags")
-                    .trim_end_matches("bag")
-                    .trim_end()
-                    .splitn(2, ' ')
-                    .collect::<Vec<&str>>();
-                let object_count = object_parts[0].parse::<usize>().unwrap();
-                let object_color = object_parts[1];
+    fn part1(bags: &Bags) -> String {
+        let mut super_colors: HashSet<&str> = HashSet::new();
+        find_super_colors(MY_COLOR, &bags.0, &mut super_colors);
+        super_colors.len().to_string()
+    }
+
+    fn part2(bags: &Bags) -> String {
+        (find_total_bag_count(MY_COLOR, &bags.1) - 1).to_string()
+    }
+
+    fn parse(input: &str) -> Bags<'_> {
+        // store key `is contained in` value relationships
+        let mut is_contained_in: MultiMap<&str, &str> = MultiMap::new();
+        let mut contains: HashMap<&str, HashMap<&str, usize>> = HashMap::new();
+
+        for line in parse::lines(input) {
+            let (subject, objects) = rule(line).unwrap_or_else(|e| panic!("day 7 input: {}", e));
+            for (object_count, object_color) in objects {
                 is_contained_in.insert(object_color, subject);
                 match contains.get_mut(subject) {
                     None => {
@@ -456,47 +2762,59 @@ fn day7() {
                 }
             }
         }
+        (is_contained_in, contains)
     }
 
-    let my_color = "shiny gold";
-
-    let mut super_colors: HashSet<&str> = HashSet::new();
-    find_super_colors(my_color, &is_contained_in, &mut super_colors);
-    println!(
-        "A total of {} colors of bags may indirectly contain a {} bag",
-        super_colors.len(),
-        my_color
-    );
-    let inner_bag_count = find_total_bag_count(my_color, &contains) - 1; // minus my_color bag
-    println!("A {} bag contains {} inner bags", my_color, inner_bag_count);
-}
+    // "muted lime bags contain 1 wavy lime bag, 1 vibrant green bag, 3 light yellow bags."
+    // "dotted teal bags contain no other bags."
+    fn rule<'a>(line: parse::Line<'a>) -> parse::Result<(&'a str, Vec<(usize, &'a str)>)> {
+        let (subject, objects) = line.split_once(line.text, " bags contain ")?;
+        if objects == "no other bags." {
+            return Ok((subject, Vec::new()));
+        }
+        let mut counts = Vec::new();
+        for object in objects.split(", ") {
+            let object = object
+                .trim_end_matches(|c| c == '.' || c == ',')
+                .trim_end_matches("bags")
+                .trim_end_matches("bag")
+                .trim_end();
+            let (count, color) = line.split_once(object, " ")?;
+            counts.push((line.parse_at(count)?, color));
+        }
+        Ok((subject, counts))
+    }
 
-fn find_total_bag_count<'a>(color: &str, contains: &HashMap<&str, HashMap<&str, usize>>) -> usize {
-    match contains.get(color) {
-        None => 1, // just me myself, ma'am
-        Some(inners) => {
-            let mut icc = 1; // inner color count; starting with just me
-            for (inner, count) in inners {
-                if &color != inner {
-                    icc = icc + count * find_total_bag_count(inner, &contains);
+    fn find_total_bag_count<'a>(
+        color: &str,
+        contains: &HashMap<&str, HashMap<&str, usize>>,
+    ) -> usize {
+        match contains.get(color) {
+            None => 1, // just me myself, ma'am
+            Some(inners) => {
+                let mut icc = 1; // inner color count; starting with just me
+                for (inner, count) in inners {
+                    if &color != inner {
+                        icc = icc + count * find_total_bag_count(inner, &contains);
+                    }
                 }
+                return icc;
             }
-            return icc;
         }
     }
-}
 
-fn find_super_colors<'a>(
-    color: &str,
-    is_contained_in: &MultiMap<&str, &'a str>,
-    mut super_colors: &mut HashSet<&'a str>,
-) {
-    match is_contained_in.get_vec(color) {
-        None => (),
-        Some(colors) => {
-            for c in colors {
-                if super_colors.insert(c) {
-                    find_super_colors(c, &is_contained_in, &mut super_colors);
+    fn find_super_colors<'a>(
+        color: &str,
+        is_contained_in: &MultiMap<&str, &'a str>,
+        mut super_colors: &mut HashSet<&'a str>,
+    ) {
+        match is_contained_in.get_vec(color) {
+            None => (),
+            Some(colors) => {
+                for c in colors {
+                    if super_colors.insert(c) {
+                        find_super_colors(c, &is_contained_in, &mut super_colors);
+                    }
                 }
             }
         }
@@ -504,6 +2822,7 @@ fn find_super_colors<'a>(
 }
 
 mod day8 {
+    use crate::registry::{self, Outcome};
     use std::collections::HashSet;
     use std::str::FromStr;
 
@@ -553,11 +2872,9 @@ mod day8 {
         if index == cmds.len() {
             Ok(0)
         } else if seen.contains(&index) {
-            eprintln!("Found loop at index {}", index);
             Err(ExecutionError)
         } else {
             seen.insert(index);
-            eprintln!("Executing {:?}", &cmds[index]);
             match &cmds[index].op {
                 Operation::Jmp(offset)
                     if (index as isize + offset) as usize > 0
@@ -575,7 +2892,6 @@ mod day8 {
     }
 
     fn flip(index: usize, cmds: &mut Vec<Command>) {
-        eprintln!("Flipping {:?}", cmds[index]);
         cmds[index].op = match cmds[index].op {
             Operation::Jmp(value) => Operation::Nop(value),
             Operation::Nop(value) => Operation::Jmp(value),
@@ -584,26 +2900,63 @@ mod day8 {
     }
 
     // day 8
-    pub fn day8() {
-        let input = include_str!("8.input");
+    pub fn day8(input: &str) {
+        let mut cmds = parse(input);
+        if let Some(accum) = repair(&mut cmds) {
+            println!("Terminated with accumulator value {}", accum);
+        }
+    }
+
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
 
-        let mut cmds = input
+    fn part1(cmds: &[Command]) -> String {
+        accumulator_at_loop(cmds).to_string()
+    }
+
+    fn part2(cmds: &[Command]) -> String {
+        repair(&mut cmds.to_vec()).map_or(String::from("none"), |a| a.to_string())
+    }
+
+    fn parse(input: &str) -> Vec<Command> {
+        input
             .lines()
             .enumerate()
             .map(|(i, s)| Command::new(i, s.parse::<Operation>().unwrap()))
-            .collect::<Vec<Command>>();
+            .collect::<Vec<Command>>()
+    }
+
+    // the accumulator value just before any instruction runs a second time
+    fn accumulator_at_loop(cmds: &[Command]) -> isize {
+        let mut seen = HashSet::new();
+        let mut index = 0;
+        let mut accum = 0;
+        while index < cmds.len() && seen.insert(index) {
+            match cmds[index].op {
+                Operation::Acc(addend) => {
+                    accum += addend;
+                    index += 1;
+                }
+                Operation::Jmp(offset) => index = (index as isize + offset) as usize,
+                Operation::Nop(_) => index += 1,
+            }
+        }
+        accum
+    }
 
+    // flips one nop/jmp at a time until the program terminates
+    fn repair(cmds: &mut Vec<Command>) -> Option<isize> {
         for index in 0..cmds.len() {
             match cmds[index].op {
                 Operation::Nop(_) | Operation::Jmp(_) => {
-                    flip(index, &mut cmds);
-                    match execute(0, &cmds, &mut HashSet::new()) {
+                    flip(index, cmds);
+                    match execute(0, cmds, &mut HashSet::new()) {
                         Ok(accum) => {
-                            println!("Terminated with accumulator value {}", accum);
-                            break;
+                            return Some(accum);
                         }
                         _ => {
-                            flip(index, &mut cmds);
+                            flip(index, cmds);
                             continue;
                         }
                     }
@@ -611,14 +2964,40 @@ mod day8 {
                 _ => continue,
             }
         }
+        None
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const INPUT1: &str = "nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
+
+        #[test]
+        fn example() {
+            let cmds = parse(INPUT1);
+            assert_eq!(accumulator_at_loop(&cmds), 5);
+            assert_eq!(repair(&mut cmds.clone()), Some(8));
+        }
     }
 }
 
 mod day9 {
+    use crate::parse;
+    use crate::registry::{self, Outcome};
+    use crate::scale::Rng;
 
     const PREAMBLE: usize = 25;
 
-    fn is_valid(index: usize, nums: &Vec<usize>) -> bool {
+    fn is_valid(index: usize, nums: &[usize]) -> bool {
         for i in (index - 25)..index - 1 {
             for j in i..index {
                 if nums[i] + nums[j] == nums[index] {
@@ -630,7 +3009,7 @@ mod day9 {
     }
 
     // only works on sorted lists, but preamble may not be sorted...
-    fn creep_for_sum(val: usize, nums: &Vec<usize>) -> (usize, usize) {
+    fn creep_for_sum(val: usize, nums: &[usize]) -> (usize, usize) {
         let mut tail = 0;
         let mut head = 1;
         let mut runsum = nums[tail] + nums[head];
@@ -663,10 +3042,8 @@ mod day9 {
         (min, max)
     }
 
-    pub fn day9() {
-        let input = include_str!("9.input");
-
-        let nums: Vec<usize> = input.lines().map(|l| l.parse::<usize>().unwrap()).collect();
+    pub fn day9(input: &str) {
+        let nums = parse(input);
 
         for i in PREAMBLE..(nums.len() - 1) {
             if !is_valid(i, &nums) {
@@ -679,15 +3056,85 @@ mod day9 {
             }
         }
     }
+
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
+
+    fn part1(nums: &[usize]) -> String {
+        first_invalid(nums).map_or(String::from("none"), |i| nums[i].to_string())
+    }
+
+    fn part2(nums: &[usize]) -> String {
+        first_invalid(nums).map_or(String::from("none"), |i| {
+            let (min, max) = creep_for_sum(nums[i], nums);
+            (min + max).to_string()
+        })
+    }
+
+    fn parse(input: &str) -> Vec<usize> {
+        parse::numbers(input).unwrap_or_else(|e| panic!("day 9 input: {}", e))
+    }
+
+    // `size` numbers, each after the preamble a sum of two in the window before it, except one
+    // near the end: the sum of a run of earlier numbers. Every tenth number is a zero (0 + 0),
+    // so when a sum gets too big a number can be repeated (0 + y) instead. Sums only use
+    // non-zero numbers, or the list soon fills up with zeros.
+    pub fn generate(size: usize, rng: &mut Rng) -> String {
+        let size = size.max(PREAMBLE + 10);
+        let mut nums = (0..PREAMBLE)
+            .map(|_| rng.range(1, 1000))
+            .collect::<Vec<usize>>();
+        nums[PREAMBLE - 5] = 0;
+        while nums.len() < size {
+            let index = nums.len();
+            let next = if index % 10 == 0 {
+                0
+            } else {
+                let nonzero = |rng: &mut Rng, lo: usize, hi: usize| loop {
+                    let p = rng.range(lo, hi);
+                    if nums[p] != 0 {
+                        break p;
+                    }
+                };
+                let i = nonzero(rng, index - PREAMBLE, index - 1);
+                let j = nonzero(rng, i, index);
+                match nums[i] + nums[j] {
+                    sum if sum < 1 << 40 => sum,
+                    _ => nums[nonzero(rng, index - PREAMBLE, index)],
+                }
+            };
+            nums.push(next);
+        }
+
+        let at = size - 5;
+        loop {
+            let start = rng.range(0, at / 2);
+            let len = rng.range(2, 6);
+            nums[at] = nums[start..start + len].iter().sum();
+            if !is_valid(at, &nums) {
+                break;
+            }
+        }
+        nums.iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn first_invalid(nums: &[usize]) -> Option<usize> {
+        (PREAMBLE..(nums.len() - 1)).find(|&i| !is_valid(i, nums))
+    }
 }
 
 // day 10
 mod day10 {
-    pub fn day10() {
-        let adapters = include_str!("10.input")
-            .lines()
-            .map(|l| l.parse::<usize>().unwrap())
-            .collect::<Vec<usize>>();
+    use crate::parse;
+    use crate::registry::{self, Outcome};
+    use crate::scale::Rng;
+
+    pub fn day10(input: &str) {
+        let adapters = parse(input);
         if let Some(stats) = joltage_summary_stat(0, &mut adapters.clone()) {
             println!(
                 "The product of one-jumps and three-jumps is {}",
@@ -703,6 +3150,53 @@ mod day10 {
         );
     }
 
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
+
+    fn part1(adapters: &[usize]) -> String {
+        joltage_summary_stat(0, &mut adapters.to_vec()).map_or(String::from("none"), |stats| {
+            (stats.0 * stats.1).to_string()
+        })
+    }
+
+    fn part2(adapters: &[usize]) -> String {
+        joltage_combo_count(&mut adapters.to_vec()).to_string()
+    }
+
+    fn parse(input: &str) -> Vec<usize> {
+        parse::numbers(input).unwrap_or_else(|e| panic!("day 10 input: {}", e))
+    }
+
+    // `size` adapters, each 1 or 3 jolts above the one before, in shuffled order. A run of n
+    // 1-jolt steps can be crossed in tribonacci(n) ways and part 2 multiplies those together,
+    // so runs (of up to 4, like the puzzle's) only grow while the product fits in a u64.
+    pub fn generate(size: usize, rng: &mut Rng) -> String {
+        const WAYS: [u64; 5] = [1, 1, 2, 4, 7]; // through a run of 0..=4 1-jolt steps
+        let mut adapters = Vec::with_capacity(size);
+        let mut joltage = 0;
+        let mut ways = 1u64; // through the runs before this one
+        let mut run = 0;
+        for _ in 0..size {
+            let longer = run + 1 < WAYS.len() && ways.checked_mul(WAYS[run + 1]).is_some();
+            joltage += if longer && rng.chance(60) {
+                run += 1;
+                1
+            } else {
+                ways *= WAYS[run];
+                run = 0;
+                3
+            };
+            adapters.push(joltage);
+        }
+        rng.shuffle(&mut adapters);
+        adapters
+            .iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
     // requires `adapters` be sorted - Vec::is_sorted() eXperimental in stable...
     pub fn joltage_summary_stat(
         output: usize,
@@ -815,10 +3309,24 @@ mod day10 {
         adapters.sort();
         assert_eq!(joltage_combo_count(&mut adapters), 19208);
     }
+
+    #[test]
+    fn test_generate() {
+        let mut rng = Rng::new(10);
+        let small = parse(&generate(20, &mut rng));
+        assert!(joltage_combo_count(&mut small.clone()) > 1);
+        assert!(joltage_summary_stat(0, &mut small.clone()).is_some());
+        // long enough to use up the u64, which would overflow in a debug build
+        let large = parse(&generate(4000, &mut rng));
+        assert!(joltage_combo_count(&mut large.clone()) > 1 << 60);
+    }
 }
 
 mod day11 {
-    use std::{cmp::min, fmt, str::FromStr};
+    use crate::grid::Grid;
+    use crate::registry::{self, Outcome};
+    use crate::render::{self, GridRender, ImageFormat, Rgb};
+    use std::{fmt, path::Path, str::FromStr};
 
     #[derive(Clone, Debug, Copy, PartialEq)]
     enum SeatStatus {
@@ -839,9 +3347,7 @@ mod day11 {
 
     #[derive(Clone, Debug, PartialEq)]
     struct SeatMap {
-        width: usize,
-        height: usize,
-        map: Vec<Vec<SeatStatus>>,
+        map: Grid<SeatStatus>,
     }
 
     impl FromStr for SeatStatus {
@@ -858,85 +3364,19 @@ mod day11 {
 
     // indices are height (rows), width (cols)
     impl SeatMap {
-        fn new(width: usize, height: usize) -> Self {
-            let mut row = Vec::with_capacity(width);
-            for _ in 0..width {
-                row.push(SeatStatus::Empty)
-            }
-            let mut map = Vec::with_capacity(height);
-            for _ in 0..height {
-                map.push(row.clone());
-            }
-            Self { width, height, map }
-        }
-
-        fn update(self: &mut Self, r: usize, c: usize, status: SeatStatus) {
-            *(self.map.get_mut(r).unwrap().get_mut(c).unwrap()) = status;
-        }
-
         fn get(self: &Self, r: usize, c: usize) -> Option<SeatStatus> {
-            if r < self.height && c < self.width {
-                Some(self.map[r][c])
-            } else {
-                None
-            }
+            self.map.get((r, c)).copied()
         }
 
         fn adjacents(self: &Self, r: usize, c: usize) -> Vec<SeatStatus> {
-            let rlb = if r == 0 { 0 } else { r - 1 };
-            let clb = if c == 0 { 0 } else { c - 1 };
-            let rub = min(r + 1, self.height - 1);
-            let cub = min(c + 1, self.width - 1);
-
-            let mut adjacents: Vec<SeatStatus> = Vec::new();
-            for ri in rlb..=rub {
-                for ci in clb..=cub {
-                    if ri == r && ci == c {
-                        continue;
-                    }
-                    adjacents.push(self.get(ri, ci).unwrap());
-                }
-            }
-            adjacents
+            self.map.neighbours8((r, c)).map(|p| self.map[p]).collect()
         }
 
         fn seats_in_los(self: &Self, r: usize, c: usize) -> Vec<SeatStatus> {
-            let mut dirs: Vec<(isize, isize)> = vec![];
-            for i in -1..=1 {
-                for j in -1..=1 {
-                    if i == 0 && j == 0 {
-                        continue;
-                    }
-                    dirs.push((i, j));
-                }
-            }
-
-            let mut seats: Vec<SeatStatus> = Vec::new();
-            'dir: for dir in dirs {
-                let mut pos = (r as isize, c as isize);
-                loop {
-                    if let Some(new_r) = pos.0.checked_add(dir.0) {
-                        if let Some(new_c) = pos.1.checked_add(dir.1) {
-                            if let Some(status) = self.get(new_r as usize, new_c as usize) {
-                                if status != SeatStatus::Floor {
-                                    seats.push(status);
-                                    continue 'dir;
-                                } else {
-                                    pos = (new_r, new_c);
-                                    continue;
-                                }
-                            } else {
-                                continue 'dir;
-                            }
-                        } else {
-                            continue 'dir;
-                        }
-                    } else {
-                        continue 'dir;
-                    }
-                }
-            }
-            seats
+            self.map
+                .line_of_sight((r, c), |&s| s == SeatStatus::Floor)
+                .map(|p| self.map[p])
+                .collect()
         }
 
         fn update_seat(self: &Self, r: usize, c: usize, adj: bool) -> SeatStatus {
@@ -967,26 +3407,16 @@ mod day11 {
         }
 
         fn update_map(self: &Self, adj: bool) -> Self {
-            let mut new = self.clone();
-            for r in 0..self.height {
-                for c in 0..self.width {
-                    let new_status = self.update_seat(r, c, adj);
-                    new.update(r, c, new_status);
-                }
+            SeatMap {
+                map: self.map.map(|(r, c), _| self.update_seat(r, c, adj)),
             }
-            new
         }
 
         pub fn occupied(self: &Self) -> usize {
-            let mut count = 0;
-            for row in &self.map {
-                for val in row {
-                    if *val == SeatStatus::Occupied {
-                        count += 1;
-                    }
-                }
-            }
-            count
+            self.map
+                .iter()
+                .filter(|&&s| s == SeatStatus::Occupied)
+                .count()
         }
 
         pub fn finally_occupied(self: &SeatMap, adj: bool) -> usize {
@@ -1000,41 +3430,69 @@ mod day11 {
 
             next.occupied()
         }
+
+        // every map from this one up to and including the first stable one
+        fn generations(self: &SeatMap, adj: bool) -> Vec<SeatMap> {
+            let mut maps = vec![self.clone()];
+            loop {
+                let next = maps.last().unwrap().update_map(adj);
+                if &next == maps.last().unwrap() {
+                    return maps;
+                }
+                maps.push(next);
+            }
+        }
+    }
+
+    impl GridRender for SeatMap {
+        fn dimensions(&self) -> (usize, usize) {
+            (self.map.height(), self.map.width())
+        }
+
+        fn cell_color(&self, row: usize, col: usize) -> Rgb {
+            match self.get(row, col).unwrap() {
+                SeatStatus::Floor => render::WHITE,
+                SeatStatus::Empty => render::GREY,
+                SeatStatus::Occupied => render::PALETTE[0],
+            }
+        }
+    }
+
+    pub fn export(
+        input: &str,
+        dir: &Path,
+        format: ImageFormat,
+        scale: usize,
+    ) -> std::io::Result<()> {
+        let map = input.parse::<SeatMap>().unwrap();
+        for (adj, prefix) in &[(true, "day11_adjacent"), (false, "day11_visible")] {
+            let generations = map.generations(*adj);
+            let frames = generations
+                .iter()
+                .map(|g| g as &dyn GridRender)
+                .collect::<Vec<_>>();
+            render::write_frames(&frames, dir, prefix, format, scale)?;
+        }
+        Ok(())
     }
 
     #[derive(Debug)]
     struct ParseError;
     impl FromStr for SeatMap {
-        type Err = ParseError;
+        type Err = crate::parse::ParseError;
         fn from_str(s: &str) -> Result<Self, Self::Err> {
-            let lines = s.lines().collect::<Vec<&str>>();
-            let width = lines[0].len();
-            let height = lines.len();
-            let mut map = SeatMap::new(width, height);
-            for (i, line) in lines.iter().enumerate() {
-                for j in 0..line.len() {
-                    let c: &str = line.get(j..j + 1).unwrap();
-                    map.update(i, j, c.parse::<SeatStatus>().unwrap());
-                }
-            }
-            Ok(map)
+            let map = Grid::from_chars(s, |c| c.to_string().parse::<SeatStatus>().ok())?;
+            Ok(SeatMap { map })
         }
     }
 
     impl fmt::Display for SeatMap {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            for i in 0..self.height {
-                for j in 0..self.width {
-                    write!(f, "{}", self.get(i, j).unwrap())?;
-                }
-                write!(f, "\n")?;
-            }
-            write!(f, "\n")
+            writeln!(f, "{}", self.map)
         }
     }
 
-    pub fn day11() {
-        let input = include_str!("11.input");
+    pub fn day11(input: &str) {
         let map = input.parse::<SeatMap>().unwrap();
         println!(
             "Using adjacency, {} seats were finally occupied",
@@ -1046,6 +3504,24 @@ mod day11 {
         );
     }
 
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
+
+    fn part1(map: &SeatMap) -> String {
+        map.finally_occupied(true).to_string()
+    }
+
+    fn part2(map: &SeatMap) -> String {
+        map.finally_occupied(false).to_string()
+    }
+
+    fn parse(input: &str) -> SeatMap {
+        input
+            .parse::<SeatMap>()
+            .unwrap_or_else(|e| panic!("day 11 input: {}", e))
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -1083,8 +3559,8 @@ L.LLLLL.LL";
         #[test]
         fn parse() {
             let map = INPUT1.parse::<SeatMap>().unwrap();
-            assert_eq!(map.width, 10);
-            assert_eq!(map.height, 10);
+            assert_eq!(map.map.width(), 10);
+            assert_eq!(map.map.height(), 10);
             assert_eq!(map.occupied(), 0);
         }
 
@@ -1118,17 +3594,25 @@ L.LLLLL.LL";
             let map = INPUT1.parse::<SeatMap>().unwrap();
             assert_eq!(map.finally_occupied(false), 26);
         }
+
+        #[test]
+        fn generations() {
+            let map = INPUT1.parse::<SeatMap>().unwrap();
+            let maps = map.generations(true);
+            assert_eq!(maps[0], map);
+            assert_eq!(maps[1], INPUT2.parse::<SeatMap>().unwrap());
+            assert_eq!(maps.last().unwrap().occupied(), 37);
+        }
     }
 }
 
 mod day12 {
+    use crate::parse;
+    use crate::registry::{self, Outcome};
     use std::str::FromStr;
 
-    pub fn day12() {
-        let commands = include_str!("12.input")
-            .lines()
-            .map(|l| l.parse::<Command>().unwrap())
-            .collect();
+    pub fn day12(input: &str) {
+        let commands = parse(input);
 
         println!("The Manhattan distance is {}", distance(&commands));
         println!(
@@ -1137,6 +3621,25 @@ mod day12 {
         );
     }
 
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
+
+    fn part1(commands: &[Command]) -> String {
+        distance(commands).to_string()
+    }
+
+    fn part2(commands: &[Command]) -> String {
+        distance2(commands).to_string()
+    }
+
+    fn parse(input: &str) -> Vec<Command> {
+        parse::lines(input)
+            .map(|l| l.parse::<Command>())
+            .collect::<parse::Result<Vec<Command>>>()
+            .unwrap_or_else(|e| panic!("day 12 input: {}", e))
+    }
+
     #[derive(Debug, PartialEq)]
     pub enum Command {
         N(isize),
@@ -1157,22 +3660,23 @@ mod day12 {
     impl FromStr for Command {
         type Err = ParseError;
         fn from_str(s: &str) -> Result<Self, Self::Err> {
-            let action = &s[0..1];
-            let value = &s[1..].parse::<isize>().unwrap();
+            let mut chars = s.chars();
+            let action = chars.next().ok_or(ParseError)?;
+            let value = chars.as_str().parse::<isize>().map_err(|_| ParseError)?;
             match action {
-                "N" => Ok(Command::N(*value)),
-                "S" => Ok(Command::S(*value)),
-                "E" => Ok(Command::E(*value)),
-                "W" => Ok(Command::W(*value)),
-                "L" => Ok(Command::L(*value)),
-                "R" => Ok(Command::R(*value)),
-                "F" => Ok(Command::F(*value)),
+                'N' => Ok(Command::N(value)),
+                'S' => Ok(Command::S(value)),
+                'E' => Ok(Command::E(value)),
+                'W' => Ok(Command::W(value)),
+                'L' => Ok(Command::L(value)),
+                'R' => Ok(Command::R(value)),
+                'F' => Ok(Command::F(value)),
                 _ => Err(ParseError),
             }
         }
     }
 
-    fn distance(cmds: &Vec<Command>) -> usize {
+    fn distance(cmds: &[Command]) -> usize {
         let mut state: State = (0, 0, 0); // x, y, heading; 0 degrees is East
         for cmd in cmds {
             state = apply(&cmd, state);
@@ -1180,7 +3684,7 @@ mod day12 {
         (state.0.abs() + state.1.abs()) as usize
     }
 
-    fn distance2(cmds: &Vec<Command>) -> usize {
+    fn distance2(cmds: &[Command]) -> usize {
         let mut state: WaypointState = (0, 0, 10, 1); // x, y of ship, waypoint offset
         for cmd in cmds {
             state = apply2(&cmd, state);
@@ -1308,10 +3812,12 @@ F11";
 }
 
 mod day13 {
-    use num::Integer;
+    use crate::numtheory;
+    use crate::registry::{self, Outcome};
+    use num::{BigInt, ToPrimitive};
 
-    pub fn day13() {
-        let notes = parse(include_str!("13.input"));
+    pub fn day13(input: &str) {
+        let notes = parse(input);
         let (bus, earliest) = find_earliest(notes.0, notes.1.clone());
 
         println!(
@@ -1319,10 +3825,24 @@ mod day13 {
             bus * (earliest - notes.0)
         );
 
-        println!(
-            "sequential bus arrivals first happen at {}",
-            find_sequential(notes.1, 10000000000000)
-        );
+        let start = find_sequential(notes.1.clone(), 10000000000000);
+        for (offset, bus) in notes.1.iter().enumerate().skip(1) {
+            print_bus(*bus, offset, start);
+        }
+        println!("sequential bus arrivals first happen at {}", start);
+    }
+
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
+
+    fn part1(notes: &(usize, Vec<usize>)) -> String {
+        let (bus, earliest) = find_earliest(notes.0, notes.1.clone());
+        (bus * (earliest - notes.0)).to_string()
+    }
+
+    fn part2(notes: &(usize, Vec<usize>)) -> String {
+        find_sequential(notes.1.clone(), 10000000000000).to_string()
     }
 
     fn parse(s: &str) -> (usize, Vec<usize>) {
@@ -1344,24 +3864,24 @@ mod day13 {
             .fold((0, usize::MAX), |a, b| if a.1 < b.1 { a } else { b })
     }
 
+    // first time after `start` at which bus j leaves j minutes after bus 0: t ≡ -j (mod bus j)
     fn find_sequential(buses: Vec<usize>, start: usize) -> usize {
-        let mut i = ((start / buses[0]) + 1) * buses[0];
-        let mut jump = buses[0];
-        'chance: loop {
-            for j in 1..buses.len() {
-                if buses[j] == 0 {
-                    print_bus(buses[j], j, i);
-                    continue;
-                }
-                if (i + j) % buses[j] != 0 {
-                    i += jump;
-                    continue 'chance;
-                }
-                jump = jump.lcm(&buses[j]);
-                print_bus(buses[j], j, i);
-            }
-            return i;
-        }
+        let congruences = buses
+            .iter()
+            .enumerate()
+            .filter(|(_, &bus)| bus != 0)
+            .map(|(j, &bus)| (-(j as i128), bus as i128))
+            .collect::<Vec<(i128, i128)>>();
+        let (t, period) =
+            numtheory::crt_any_size(&congruences).expect("buses never leave in sequence");
+        let start = BigInt::from(start);
+        let t = if t > start {
+            t
+        } else {
+            &t + (&start - &t) / &period * &period + &period
+        };
+        t.to_usize()
+            .expect("sequential departures are too far off to fit a usize")
     }
 
     fn print_bus(bus: usize, offset: usize, start: usize) {
@@ -1448,6 +3968,9 @@ mod day13 {
 }
 
 mod day14 {
+    use crate::parse;
+    use crate::registry::{self, Outcome};
+    use crate::scale::Rng;
     use std::collections::HashMap;
 
     /*
@@ -1463,10 +3986,79 @@ mod day14 {
      *    o 00100 (|)
      *   x' 01100
      */
-    pub fn day14() {
-        let input = include_str!("14.input");
-        println!("final sum: {}", execute(&input));
-        println!("final sum (part 2): {}", execute2(&input));
+    pub fn day14(input: &str) {
+        let program = parse(input);
+        println!("final sum: {}", execute(&program));
+        println!("final sum (part 2): {}", execute2(&program));
+    }
+
+    pub fn solve(input: &str) -> Outcome {
+        registry::run(input, parse, part1, Some(part2))
+    }
+
+    fn part1(program: &[Instruction]) -> String {
+        execute(program).to_string()
+    }
+
+    fn part2(program: &[Instruction]) -> String {
+        execute2(program).to_string()
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Instruction<'a> {
+        Mask(&'a str),
+        Mem(u64, u64), // address, value
+    }
+
+    fn parse(input: &str) -> Vec<Instruction<'_>> {
+        parse::lines(input)
+            .map(instruction)
+            .collect::<parse::Result<Vec<Instruction>>>()
+            .unwrap_or_else(|e| panic!("day 14 input: {}", e))
+    }
+
+    // 20 masks with `size` floating bits each (the other bits random), each followed by four
+    // writes to random addresses
+    pub fn generate(size: usize, rng: &mut Rng) -> String {
+        let mut lines = Vec::new();
+        for _ in 0..20 {
+            let mut mask = (0..36)
+                .map(|i| {
+                    if i < size {
+                        'X'
+                    } else if rng.chance(50) {
+                        '1'
+                    } else {
+                        '0'
+                    }
+                })
+                .collect::<Vec<char>>();
+            rng.shuffle(&mut mask);
+            lines.push(format!("mask = {}", mask.iter().collect::<String>()));
+            for _ in 0..4 {
+                let (addr, value) = (rng.range(0, 1 << 16), rng.range(1, 1 << 30));
+                lines.push(format!("mem[{}] = {}", addr, value));
+            }
+        }
+        lines.join("\n")
+    }
+
+    // "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X" or "mem[8] = 11"
+    fn instruction(line: parse::Line<'_>) -> parse::Result<Instruction<'_>> {
+        let (target, value) = line.key_value("=")?;
+        if target == "mask" {
+            return Ok(Instruction::Mask(value));
+        }
+        match target
+            .strip_prefix("mem[")
+            .and_then(|t| t.strip_suffix(']'))
+        {
+            Some(addr) => Ok(Instruction::Mem(
+                line.parse_at(addr)?,
+                line.parse_at(value)?,
+            )),
+            None => Err(line.error(target, "expected `mask` or `mem[address]`")),
+        }
     }
 
     fn parse_mask(mask: &str) -> (u64, u64) {
@@ -1484,24 +4076,20 @@ mod day14 {
     }
 
     // let's brute force!
-    fn execute2(s: &str) -> usize {
-        let mut mem: HashMap<String, usize> = HashMap::new();
+    fn execute2(program: &[Instruction]) -> u64 {
+        let mut mem: HashMap<String, u64> = HashMap::new();
         let mut mask = "bad mask";
-        for line in s.lines() {
-            match &line[..=2] {
-                "mas" => {
-                    mask = &line[7..];
+        for instruction in program {
+            match *instruction {
+                Instruction::Mask(m) => {
+                    mask = m;
                 }
-                "mem" => {
-                    let lbindex = line.find(']').unwrap();
-                    let loc = &line[4..lbindex];
-                    let val = line[lbindex + 4..].parse::<usize>().unwrap();
-                    let expanded_addrs = expand_addr(&apply_mask(loc, &mask));
+                Instruction::Mem(loc, val) => {
+                    let expanded_addrs = expand_addr(&apply_mask(loc, mask));
                     for addr in expanded_addrs {
                         mem.insert(addr, val);
                     }
                 }
-                s => panic!("ahhh! {}", s),
             }
         }
 
@@ -1522,9 +4110,9 @@ mod day14 {
     }
 
     // bad code bad code wee ooo wee ooo wee ooo
-    fn apply_mask<'a>(l: &'a str, m: &'a str) -> String {
+    fn apply_mask(l: u64, m: &str) -> String {
         let mut ll = String::new();
-        let mut lo = l.parse::<usize>().unwrap();
+        let mut lo = l;
         while lo > 0 {
             ll.insert(
                 0,
@@ -1551,21 +4139,17 @@ mod day14 {
         ret
     }
 
-    fn execute(s: &str) -> u64 {
+    fn execute(program: &[Instruction]) -> u64 {
         let mut mask: (u64, u64) = (0, 0);
         let mut mem: HashMap<u64, u64> = HashMap::new();
-        for line in s.lines() {
-            match &line[..=2] {
-                "mas" => {
-                    mask = parse_mask(&line[7..].trim());
+        for instruction in program {
+            match *instruction {
+                Instruction::Mask(m) => {
+                    mask = parse_mask(m);
                 }
-                "mem" => {
-                    let lbindex = line.find(']').unwrap();
-                    let loc = line[4..lbindex].parse::<u64>().unwrap();
-                    let val = line[lbindex + 4..].parse::<u64>().unwrap();
+                Instruction::Mem(loc, val) => {
                     mem.insert(loc, val & mask.0 | mask.1);
                 }
-                s => panic!("ahhh! {}", s),
             }
         }
         mem.values().fold(0 as u64, |v1, v2| v1 + v2)
@@ -1587,17 +4171,23 @@ mem[26] = 1";
 
         #[test]
         fn test_execute() {
-            let val = execute(&INPUT1);
+            let val = execute(&parse(INPUT1));
             assert_eq!(val, 165);
         }
 
         #[test]
         fn test_execute2() {
-            println!("{}", &INPUT2);
-            let val = execute2(&INPUT2);
+            let val = execute2(&parse(INPUT2));
             assert_eq!(val, 208);
         }
 
+        #[test]
+        fn test_parse() {
+            assert_eq!(parse(INPUT1)[2], Instruction::Mem(7, 101));
+            let err = instruction(parse::lines("mem[x] = 1").next().unwrap()).unwrap_err();
+            assert_eq!((err.line, err.column), (1, 5));
+        }
+
         #[test]
         fn test_expand_addr() {
             assert_eq!(expand_addr("X0X"), vec!["000", "001", "100", "101"]);
@@ -0,0 +1,148 @@
+// Modular arithmetic for the puzzles that are secretly number theory (day 13's buses, ...).
+// The generic versions work for any signed integer type, including num's BigInt; the
+// `checked_` versions use i128 and report overflow instead of wrapping.
+use num::{BigInt, Integer, Signed};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CrtError {
+    NoSolution, // two of the congruences contradict each other
+    Overflow,
+}
+
+// (g, x, y) with a*x + b*y = g = gcd(a, b), g >= 0
+pub fn egcd<T: Integer + Signed + Clone>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (T::one(), T::zero());
+    let (mut old_t, mut t) = (T::zero(), T::one());
+    while !r.is_zero() {
+        let q = old_r.clone() / r.clone();
+        let next_r = old_r - q.clone() * r.clone();
+        old_r = std::mem::replace(&mut r, next_r);
+        let next_s = old_s - q.clone() * s.clone();
+        old_s = std::mem::replace(&mut s, next_s);
+        let next_t = old_t - q * t.clone();
+        old_t = std::mem::replace(&mut t, next_t);
+    }
+    if old_r.is_negative() {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+// x in 0..m with a*x ≡ 1 (mod m), if a and m are coprime
+pub fn mod_inverse<T: Integer + Signed + Clone>(a: T, m: T) -> Option<T> {
+    let (g, x, _) = egcd(a.mod_floor(&m), m.clone());
+    if g.is_one() {
+        Some(x.mod_floor(&m))
+    } else {
+        None
+    }
+}
+
+// Solves x ≡ r (mod m) for every (r, m). The moduli don't need to be coprime. Returns the
+// smallest non-negative x and the lcm of the moduli (every solution is x + k*lcm), or None if
+// there's no solution.
+pub fn crt<T: Integer + Signed + Clone>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    let mut acc = (T::zero(), T::one());
+    for (r, m) in congruences {
+        let (x, l) = acc;
+        let g = l.gcd(m);
+        let diff = r.clone() - x.clone();
+        if !diff.mod_floor(&g).is_zero() {
+            return None;
+        }
+        let m_g = m.clone() / g.clone();
+        let k =
+            (diff / g.clone()).mod_floor(&m_g) * mod_inverse(l.clone() / g.clone(), m_g.clone())?;
+        let k = k.mod_floor(&m_g);
+        let lcm = l.clone() / g * m.clone();
+        acc = ((x + l * k).mod_floor(&lcm), lcm);
+    }
+    Some(acc)
+}
+
+// `crt` in i128, failing with Overflow rather than wrapping when the lcm gets too big
+pub fn checked_crt(congruences: &[(i128, i128)]) -> Result<(i128, i128), CrtError> {
+    let overflow = CrtError::Overflow;
+    let mut acc: (i128, i128) = (0, 1);
+    for &(r, m) in congruences {
+        let (x, l) = acc;
+        let g = l.gcd(&m);
+        let diff = r.checked_sub(x).ok_or(overflow)?;
+        if diff.mod_floor(&g) != 0 {
+            return Err(CrtError::NoSolution);
+        }
+        let m_g = m / g;
+        let inverse = mod_inverse(l / g, m_g).ok_or(CrtError::NoSolution)?;
+        let k = (diff / g)
+            .mod_floor(&m_g)
+            .checked_mul(inverse)
+            .ok_or(overflow)?
+            .mod_floor(&m_g);
+        let lcm = (l / g).checked_mul(m).ok_or(overflow)?;
+        let x = l
+            .checked_mul(k)
+            .and_then(|lk| lk.checked_add(x))
+            .ok_or(overflow)?;
+        acc = (x.mod_floor(&lcm), lcm);
+    }
+    Ok(acc)
+}
+
+// `checked_crt`, redone with BigInts if i128 isn't big enough; None if there's no solution
+pub fn crt_any_size(congruences: &[(i128, i128)]) -> Option<(BigInt, BigInt)> {
+    match checked_crt(congruences) {
+        Ok((x, lcm)) => Some((BigInt::from(x), BigInt::from(lcm))),
+        Err(CrtError::NoSolution) => None,
+        Err(CrtError::Overflow) => crt(&congruences
+            .iter()
+            .map(|&(r, m)| (BigInt::from(r), BigInt::from(m)))
+            .collect::<Vec<(BigInt, BigInt)>>()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use num::BigInt;
+
+    #[test]
+    fn test_egcd() {
+        assert_eq!(egcd(240, 46), (2, -9, 47));
+        assert_eq!(egcd(-4, 6), (2, 1, 1));
+        assert_eq!(egcd(0, 5), (5, 0, 1));
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // not coprime, but consistent
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt::<i64>(&[]), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_checked_crt() {
+        assert_eq!(checked_crt(&[(2, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        assert_eq!(checked_crt(&[(1, 4), (2, 6)]), Err(CrtError::NoSolution));
+        let big_primes = [(1, (1i128 << 61) - 1), (2, (1i128 << 89) - 1)];
+        assert_eq!(checked_crt(&big_primes), Err(CrtError::Overflow));
+
+        // the same system has no trouble as BigInts
+        let (x, lcm) = crt_any_size(&big_primes).unwrap();
+        let (m1, m2) = (BigInt::from(big_primes[0].1), BigInt::from(big_primes[1].1));
+        assert_eq!(&x % &m1, BigInt::from(1));
+        assert_eq!(&x % &m2, BigInt::from(2));
+        assert_eq!(lcm, m1 * m2);
+        assert_eq!(crt_any_size(&[(1, 4), (2, 6)]), None);
+    }
+}
@@ -0,0 +1,249 @@
+// Input-parsing helpers shared by the days: blank-line separated records and sections, number
+// lists, "key: value" pairs, and errors that point at the line and column that didn't parse.
+use std::fmt;
+use std::str::FromStr;
+
+// line and column are 1-based, like an editor shows them
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+// One line of input that remembers where it came from, so anything parsed out of it can
+// report a position. Methods that take a `part` expect a slice of `text`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn error(&self, part: &str, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.number,
+            column: self.column_of(part),
+            message: message.into(),
+        }
+    }
+
+    // 1-based column where `part` starts, or 1 if it isn't a slice of this line
+    fn column_of(&self, part: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let at = part.as_ptr() as usize;
+        if at >= start && at <= start + self.text.len() {
+            self.text[..at - start].chars().count() + 1
+        } else {
+            1
+        }
+    }
+
+    pub fn parse<T: FromStr>(&self) -> Result<T>
+    where
+        T::Err: fmt::Debug,
+    {
+        self.parse_at(self.text)
+    }
+
+    pub fn parse_at<T: FromStr>(&self, part: &'a str) -> Result<T>
+    where
+        T::Err: fmt::Debug,
+    {
+        part.parse::<T>()
+            .map_err(|e| self.error(part, format!("can't parse {:?} ({:?})", part, e)))
+    }
+
+    // `part` split around the first `sep`, e.g. "1-3" around '-'
+    pub fn split_once(&self, part: &'a str, sep: &str) -> Result<(&'a str, &'a str)> {
+        match part.find(sep) {
+            Some(i) => Ok((&part[..i], &part[i + sep.len()..])),
+            None => Err(self.error(part, format!("expected {:?} in {:?}", sep, part))),
+        }
+    }
+
+    // "key: value" style lines; whitespace around the value is dropped
+    pub fn key_value(&self, sep: &str) -> Result<(&'a str, &'a str)> {
+        let (key, value) = self.split_once(self.text, sep)?;
+        Ok((key.trim_end(), value.trim()))
+    }
+
+    // whitespace-separated "key:value" tokens, as in passports
+    pub fn pairs(&self, sep: &str) -> Result<Vec<(&'a str, &'a str)>> {
+        self.text
+            .split_whitespace()
+            .map(|token| self.split_once(token, sep))
+            .collect()
+    }
+
+    // values separated by `sep`, e.g. "7,1,14"
+    pub fn list<T: FromStr>(&self, sep: char) -> Result<Vec<T>>
+    where
+        T::Err: fmt::Debug,
+    {
+        self.text
+            .split(sep)
+            .map(|item| self.parse_at(item.trim()))
+            .collect()
+    }
+}
+
+// every line, numbered from 1, with any '\r' from CRLF files stripped
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    input.lines().enumerate().map(|(i, text)| Line {
+        number: i + 1,
+        text: text.trim_end_matches('\r'),
+    })
+}
+
+// Runs of lines separated by one or more blank lines. Leading/trailing blank lines don't make
+// empty records, so a missing or extra newline at the end of the input doesn't matter.
+pub fn records(input: &str) -> Vec<Vec<Line<'_>>> {
+    let mut records = vec![Vec::new()];
+    for line in lines(input) {
+        if line.text.trim().is_empty() {
+            if !records.last().unwrap().is_empty() {
+                records.push(Vec::new());
+            }
+        } else {
+            records.last_mut().unwrap().push(line);
+        }
+    }
+    if records.last().unwrap().is_empty() {
+        records.pop();
+    }
+    records
+}
+
+// like `records`, but the input must have exactly `count` of them
+pub fn sections(input: &str, count: usize) -> Result<Vec<Vec<Line<'_>>>> {
+    let sections = records(input);
+    if sections.len() == count {
+        Ok(sections)
+    } else {
+        let last = lines(input).last().map_or(1, |l| l.number);
+        Err(ParseError {
+            line: last,
+            column: 1,
+            message: format!(
+                "expected {} blank-line separated sections, found {}",
+                count,
+                sections.len()
+            ),
+        })
+    }
+}
+
+// the lines of a section after its `header` line, e.g. "your ticket:"; there must be some
+pub fn body<'s, 'a>(section: &'s [Line<'a>], header: &str) -> Result<&'s [Line<'a>]> {
+    match section.split_first() {
+        Some((first, [])) if first.text.trim() == header => {
+            Err(first.error(first.text, format!("expected lines after {:?}", header)))
+        }
+        Some((first, rest)) if first.text.trim() == header => Ok(rest),
+        Some((first, _)) => Err(first.error(first.text, format!("expected {:?}", header))),
+        // `records` never makes an empty section, so there's no header line to point at
+        None => Err(ParseError {
+            line: 1,
+            column: 1,
+            message: format!("expected {:?}, found an empty section", header),
+        }),
+    }
+}
+
+// one value per non-blank line
+pub fn numbers<T: FromStr>(input: &str) -> Result<Vec<T>>
+where
+    T::Err: fmt::Debug,
+{
+    lines(input)
+        .filter(|l| !l.text.trim().is_empty())
+        .map(|l| l.parse_at(l.text.trim()))
+        .collect()
+}
+
+// values separated by `sep`, over however many lines there are
+pub fn list<T: FromStr>(input: &str, sep: char) -> Result<Vec<T>>
+where
+    T::Err: fmt::Debug,
+{
+    let mut values = Vec::new();
+    for line in lines(input).filter(|l| !l.text.trim().is_empty()) {
+        values.append(&mut line.list(sep)?);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        let records = records("\na b\nc\n\n\nd\n\n");
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].len(), 2);
+        assert_eq!(
+            records[1][0],
+            Line {
+                number: 6,
+                text: "d"
+            }
+        );
+        assert!(sections("a\n\nb", 3).is_err());
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(numbers::<usize>("1\r\n2\n\n3\n"), Ok(vec![1, 2, 3]));
+        assert_eq!(list::<usize>("20,9, 11\n", ','), Ok(vec![20, 9, 11]));
+        let err = numbers::<usize>("1\n2\nx3").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 1));
+        let err = list::<usize>("1,2,three", ',').unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
+    }
+
+    #[test]
+    fn test_key_values() {
+        let line = lines("ecl:gry pid:860033327  eyr:2020").next().unwrap();
+        assert_eq!(
+            line.pairs(":"),
+            Ok(vec![("ecl", "gry"), ("pid", "860033327"), ("eyr", "2020")])
+        );
+        let line = lines("x\nrow: 6-11 or 33-44").nth(1).unwrap();
+        assert_eq!(line.key_value(":"), Ok(("row", "6-11 or 33-44")));
+        let err = line.split_once(&line.text[5..], "/").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 6: expected \"/\" in \"6-11 or 33-44\""
+        );
+    }
+
+    #[test]
+    fn test_body() {
+        let sections = sections("your ticket:\n7,1,14\n\nnearby:\n1", 2).unwrap();
+        assert_eq!(body(&sections[0], "your ticket:").unwrap().len(), 1);
+        let err = body(&sections[1], "nearby tickets:").unwrap_err();
+        assert_eq!(err.line, 4);
+
+        let sections = super::sections("a\n\n\nyour ticket:\n", 2).unwrap();
+        let err = body(&sections[1], "your ticket:").unwrap_err();
+        assert_eq!((err.line, err.column), (4, 1));
+        assert_eq!(body(&[], "your ticket:").unwrap_err().line, 1);
+        assert_eq!(super::sections("", 1).unwrap_err().line, 1);
+    }
+}
@@ -0,0 +1,195 @@
+// Lightweight phase timing. Solutions mark phases with `let _span = profile::span("name");`
+// and the time until the guard drops is added to a tree of spans; spans with the same name
+// under the same parent (e.g. once per loop iteration) are merged. Nothing is recorded unless
+// the code runs inside `record`, so the spans cost next to nothing otherwise.
+use crate::report::format_duration;
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub name: String,
+    pub calls: usize,
+    pub total: Duration,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    fn new(name: &str) -> Self {
+        Node {
+            name: String::from(name),
+            calls: 0,
+            total: Duration::default(),
+            children: Vec::new(),
+        }
+    }
+}
+
+struct Recorder {
+    root: Node,
+    path: Vec<usize>, // child indices from the root down to the open span
+}
+
+impl Recorder {
+    fn current(&mut self) -> &mut Node {
+        let mut node = &mut self.root;
+        for &i in &self.path {
+            node = &mut node.children[i];
+        }
+        node
+    }
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+// None when nothing is being recorded
+pub struct Span {
+    start: Option<Instant>,
+}
+
+pub fn span(name: &str) -> Span {
+    let recording = RECORDER.with(|r| match r.borrow_mut().as_mut() {
+        None => false,
+        Some(recorder) => {
+            let node = recorder.current();
+            let i = match node.children.iter().position(|c| c.name == name) {
+                Some(i) => i,
+                None => {
+                    node.children.push(Node::new(name));
+                    node.children.len() - 1
+                }
+            };
+            recorder.path.push(i);
+            true
+        }
+    });
+    Span {
+        start: if recording {
+            Some(Instant::now())
+        } else {
+            None
+        },
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            let elapsed = start.elapsed();
+            RECORDER.with(|r| {
+                if let Some(recorder) = r.borrow_mut().as_mut() {
+                    let node = recorder.current();
+                    node.calls += 1;
+                    node.total += elapsed;
+                    recorder.path.pop();
+                }
+            });
+        }
+    }
+}
+
+// Runs `f` with span recording on for this thread and returns its spans under a root named
+// `name`. Not reentrant: a nested `record` takes over until it returns.
+pub fn record<T>(name: &str, f: impl FnOnce() -> T) -> (T, Node) {
+    RECORDER.with(|r| {
+        *r.borrow_mut() = Some(Recorder {
+            root: Node::new(name),
+            path: Vec::new(),
+        })
+    });
+    let start = Instant::now();
+    let value = f();
+    let elapsed = start.elapsed();
+    let mut root = RECORDER
+        .with(|r| r.borrow_mut().take())
+        .map_or_else(|| Node::new(name), |recorder| recorder.root);
+    root.calls = 1;
+    root.total = elapsed;
+    (value, root)
+}
+
+// one line per span, indented by depth, with its share of the root's time
+pub fn format_tree(root: &Node) -> String {
+    let mut rows = Vec::new();
+    flatten(root, 0, &mut rows);
+    let width = rows
+        .iter()
+        .map(|(depth, node)| 2 * depth + node.name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    for (depth, node) in rows {
+        let label = format!("{}{}", "  ".repeat(depth), node.name);
+        let share = if root.total.as_nanos() > 0 {
+            100.0 * node.total.as_secs_f64() / root.total.as_secs_f64()
+        } else {
+            0.0
+        };
+        out += &format!(
+            "{:<width$}  {:>10}  {:>5.1}%",
+            label,
+            format_duration(node.total),
+            share,
+            width = width
+        );
+        if node.calls > 1 {
+            out += &format!("  ({} calls)", node.calls);
+        }
+        out += "\n";
+    }
+    out
+}
+
+fn flatten<'a>(node: &'a Node, depth: usize, rows: &mut Vec<(usize, &'a Node)>) {
+    rows.push((depth, node));
+    for child in &node.children {
+        flatten(child, depth + 1, rows);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let (value, root) = record("day", || {
+            let _outer = span("outer");
+            for _ in 0..3 {
+                let _inner = span("inner");
+            }
+            42
+        });
+        assert_eq!(value, 42);
+        assert_eq!(root.name, "day");
+        assert_eq!(root.children.len(), 1);
+        let outer = &root.children[0];
+        assert_eq!((outer.name.as_str(), outer.calls), ("outer", 1));
+        assert_eq!(outer.children[0].calls, 3);
+        assert!(outer.total <= root.total);
+    }
+
+    #[test]
+    fn test_span_outside_record() {
+        let _span = span("ignored");
+        let (_, root) = record("day", || ());
+        assert!(root.children.is_empty());
+    }
+
+    #[test]
+    fn test_format_tree() {
+        let mut root = Node::new("day 1");
+        root.total = Duration::from_millis(10);
+        let mut child = Node::new("parse");
+        child.total = Duration::from_millis(5);
+        child.calls = 2;
+        root.children.push(child);
+        assert_eq!(
+            format_tree(&root),
+            "day 1      10.00 ms  100.0%\n  parse     5.00 ms   50.0%  (2 calls)\n"
+        );
+    }
+}
@@ -0,0 +1,249 @@
+// Every solved day in one place, so tooling (reports, timings, ...) can run them all the same way.
+use crate::days::{day15, day16, day17, day18, day19};
+use crate::profile;
+use crate::{
+    day1, day10, day11, day12, day13, day14, day2, day3, day4, day5, day6, day7, day8, day9,
+};
+use std::borrow::Borrow;
+use std::cell::Cell;
+use std::panic;
+use std::time::{Duration, Instant};
+
+pub const LAST_DAY: usize = 25;
+
+pub struct Day {
+    pub day: usize,
+    pub solve: fn(&str) -> Outcome,
+}
+
+// answers are `None` for parts that aren't solved yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+    pub parse_time: Duration,
+    pub part1_time: Duration,
+    pub part2_time: Duration,
+}
+
+thread_local! {
+    // set by `parses`, to stop `run` once the input is parsed
+    static PARSE_ONLY: Cell<bool> = const { Cell::new(false) };
+}
+
+// parses once, then runs each part against the parsed input, timing all three (and marking
+// each as a profile span)
+pub fn run<'a, P, Q>(
+    input: &'a str,
+    parse: fn(&'a str) -> P,
+    part1: fn(&Q) -> String,
+    part2: Option<fn(&Q) -> String>,
+) -> Outcome
+where
+    P: Borrow<Q>,
+    Q: ?Sized,
+{
+    let start = Instant::now();
+    let parsed = {
+        let _span = profile::span("parse");
+        parse(input)
+    };
+    let parse_time = start.elapsed();
+    if PARSE_ONLY.with(Cell::get) {
+        return Outcome {
+            part1: None,
+            part2: None,
+            parse_time,
+            part1_time: Duration::ZERO,
+            part2_time: Duration::ZERO,
+        };
+    }
+
+    let start = Instant::now();
+    let answer1 = {
+        let _span = profile::span("part 1");
+        part1(parsed.borrow())
+    };
+    let part1_time = start.elapsed();
+
+    let start = Instant::now();
+    let answer2 = part2.map(|part2| {
+        let _span = profile::span("part 2");
+        part2(parsed.borrow())
+    });
+    let part2_time = start.elapsed();
+
+    Outcome {
+        part1: Some(answer1),
+        part2: answer2,
+        parse_time,
+        part1_time,
+        part2_time,
+    }
+}
+
+// whether the day's parser takes `input`, without solving it; the panic a day gives up on bad
+// input with is caught
+pub fn parses(day: &Day, input: &str) -> bool {
+    PARSE_ONLY.with(|p| p.set(true));
+    let parsed = panic::catch_unwind(|| (day.solve)(input)).is_ok();
+    PARSE_ONLY.with(|p| p.set(false));
+    parsed
+}
+
+// What a day's answers depend on besides its input, like day 4's passport rules, so anything
+// that keeps answers can tell when they no longer hold. Empty for most days.
+pub fn settings(day: usize) -> String {
+    match day {
+        4 => day4::rules_text(),
+        _ => String::new(),
+    }
+}
+
+pub fn days() -> Vec<Day> {
+    vec![
+        Day {
+            day: 1,
+            solve: day1::solve,
+        },
+        Day {
+            day: 2,
+            solve: day2::solve,
+        },
+        Day {
+            day: 3,
+            solve: day3::solve,
+        },
+        Day {
+            day: 4,
+            solve: day4::solve,
+        },
+        Day {
+            day: 5,
+            solve: day5::solve,
+        },
+        Day {
+            day: 6,
+            solve: day6::solve,
+        },
+        Day {
+            day: 7,
+            solve: day7::solve,
+        },
+        Day {
+            day: 8,
+            solve: day8::solve,
+        },
+        Day {
+            day: 9,
+            solve: day9::solve,
+        },
+        Day {
+            day: 10,
+            solve: day10::solve,
+        },
+        Day {
+            day: 11,
+            solve: day11::solve,
+        },
+        Day {
+            day: 12,
+            solve: day12::solve,
+        },
+        Day {
+            day: 13,
+            solve: day13::solve,
+        },
+        Day {
+            day: 14,
+            solve: day14::solve,
+        },
+        Day {
+            day: 15,
+            solve: day15::solve,
+        },
+        Day {
+            day: 16,
+            solve: day16::solve,
+        },
+        Day {
+            day: 17,
+            solve: day17::solve,
+        },
+        Day {
+            day: 18,
+            solve: day18::solve,
+        },
+        Day {
+            day: 19,
+            solve: day19::solve,
+        },
+    ]
+}
+
+// "day part answer" lines; blank lines and '#' comments are skipped
+pub fn parse_answers(s: &str) -> Vec<(usize, usize, String)> {
+    s.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|l| {
+            let parts = l.splitn(3, ' ').collect::<Vec<&str>>();
+            match parts[..] {
+                [day, part, answer] => Some((day.parse().ok()?, part.parse().ok()?, answer.into())),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_registry_days() {
+        let days = days();
+        assert_eq!(days.len(), 19);
+        assert!(days.windows(2).all(|w| w[0].day + 1 == w[1].day));
+    }
+
+    #[test]
+    fn test_run() {
+        let outcome = run(
+            "1\n2\n3",
+            |s| {
+                s.lines()
+                    .map(|l| l.parse::<usize>().unwrap())
+                    .collect::<Vec<usize>>()
+            },
+            |v: &[usize]| v.iter().sum::<usize>().to_string(),
+            None,
+        );
+        assert_eq!(outcome.part1, Some(String::from("6")));
+        assert_eq!(outcome.part2, None);
+    }
+
+    #[test]
+    fn test_parses() {
+        let day1 = &days()[0];
+        assert!(parses(day1, "1721\n979\n"));
+        assert!(!parses(day1, "1721\nnope\n"));
+        // and solving works as before afterwards
+        assert_eq!(
+            (day1.solve)("1010\n1010\n").part1.as_deref(),
+            Some("1020100")
+        );
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        let answers = parse_answers("# day part answer\n1 1 42\n\n1 2 hello world\nbogus");
+        assert_eq!(
+            answers,
+            vec![
+                (1, 1, String::from("42")),
+                (1, 2, String::from("hello world"))
+            ]
+        );
+    }
+}
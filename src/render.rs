@@ -0,0 +1,361 @@
+// Image export for the grid-y days. Anything that can say how big it is and what colour a
+// cell is gets PPM/PNG output (and animated PNG for a sequence of states) for free.
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+pub type Rgb = (u8, u8, u8);
+
+pub const WHITE: Rgb = (0xff, 0xff, 0xff);
+pub const GREY: Rgb = (0xa0, 0xa0, 0xa0);
+
+// one colour per overlay (slope, path, ...), cycled if there are more overlays than colours
+pub const PALETTE: [Rgb; 6] = [
+    (0xe6, 0x19, 0x4b),
+    (0x43, 0x63, 0xd8),
+    (0xf5, 0x82, 0x31),
+    (0x91, 0x1e, 0xb4),
+    (0x42, 0xd4, 0xf4),
+    (0xf0, 0x32, 0xe6),
+];
+
+pub trait GridRender {
+    // (rows, cols)
+    fn dimensions(&self) -> (usize, usize);
+    fn cell_color(&self, row: usize, col: usize) -> Rgb;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+}
+
+impl ImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ImageFormatParseError;
+impl FromStr for ImageFormat {
+    type Err = ImageFormatParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ppm" => Ok(ImageFormat::Ppm),
+            "png" => Ok(ImageFormat::Png),
+            _ => Err(ImageFormatParseError),
+        }
+    }
+}
+
+// width and height in pixels plus packed RGB bytes, row-major
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+pub fn rasterize(grid: &dyn GridRender, scale: usize) -> Image {
+    let (rows, cols) = grid.dimensions();
+    let (width, height) = (cols * scale, rows * scale);
+    let mut pixels = Vec::with_capacity(width * height * 3);
+    for r in 0..rows {
+        let row: Vec<Rgb> = (0..cols).map(|c| grid.cell_color(r, c)).collect();
+        for _ in 0..scale {
+            for &(red, green, blue) in &row {
+                for _ in 0..scale {
+                    pixels.extend_from_slice(&[red, green, blue]);
+                }
+            }
+        }
+    }
+    Image {
+        width,
+        height,
+        pixels,
+    }
+}
+
+pub fn write_image(
+    grid: &dyn GridRender,
+    path: &Path,
+    format: ImageFormat,
+    scale: usize,
+) -> io::Result<()> {
+    let image = rasterize(grid, scale);
+    let mut out = BufWriter::new(File::create(path)?);
+    match format {
+        ImageFormat::Ppm => write_ppm(&image, &mut out),
+        ImageFormat::Png => write_png(&[image], &mut out),
+    }
+}
+
+// Writes every frame as `<prefix>_NNN.<ext>`, and for PNG also an animated `<prefix>.png`.
+// Returns the number of frames written.
+pub fn write_frames(
+    frames: &[&dyn GridRender],
+    dir: &Path,
+    prefix: &str,
+    format: ImageFormat,
+    scale: usize,
+) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    let images = frames
+        .iter()
+        .map(|f| rasterize(*f, scale))
+        .collect::<Vec<Image>>();
+    for (i, image) in images.iter().enumerate() {
+        let path = dir.join(format!("{}_{:03}.{}", prefix, i, format.extension()));
+        let mut out = BufWriter::new(File::create(path)?);
+        match format {
+            ImageFormat::Ppm => write_ppm(image, &mut out)?,
+            ImageFormat::Png => write_png(std::slice::from_ref(image), &mut out)?,
+        }
+    }
+    if format == ImageFormat::Png && !images.is_empty() {
+        let mut out = BufWriter::new(File::create(dir.join(format!("{}.png", prefix)))?);
+        write_png(&images, &mut out)?;
+    }
+    Ok(images.len())
+}
+
+pub fn write_ppm(image: &Image, out: &mut dyn Write) -> io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", image.width, image.height)?;
+    out.write_all(&image.pixels)?;
+    out.flush()
+}
+
+// Minimal PNG writer: 8-bit RGB, no filtering, deflate "stored" blocks. More than one image
+// makes an APNG as big as the largest frame; smaller frames are drawn in its top-left corner.
+pub fn write_png(images: &[Image], out: &mut dyn Write) -> io::Result<()> {
+    const FRAME_DELAY_MS: u16 = 200;
+
+    if images.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "a PNG needs at least one image",
+        ));
+    }
+    let width = images.iter().map(|i| i.width).max().unwrap_or(0);
+    let height = images.iter().map(|i| i.height).max().unwrap_or(0);
+    // the first frame is also the still image, so it has to cover the whole canvas
+    let first = padded(&images[0], width, height);
+    out.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'])?;
+
+    let mut ihdr = Vec::new();
+    ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]); // depth 8, truecolour, deflate, no filter, no interlace
+    write_chunk(out, b"IHDR", &ihdr)?;
+
+    let animated = images.len() > 1;
+    if animated {
+        let mut actl = Vec::new();
+        actl.extend_from_slice(&(images.len() as u32).to_be_bytes());
+        actl.extend_from_slice(&0u32.to_be_bytes()); // loop forever
+        write_chunk(out, b"acTL", &actl)?;
+    }
+
+    let mut seq: u32 = 0;
+    for (i, image) in images.iter().enumerate() {
+        let image = if i == 0 { &first } else { image };
+        if animated {
+            let mut fctl = Vec::new();
+            fctl.extend_from_slice(&seq.to_be_bytes());
+            fctl.extend_from_slice(&(image.width as u32).to_be_bytes());
+            fctl.extend_from_slice(&(image.height as u32).to_be_bytes());
+            fctl.extend_from_slice(&0u32.to_be_bytes()); // x offset
+            fctl.extend_from_slice(&0u32.to_be_bytes()); // y offset
+            fctl.extend_from_slice(&FRAME_DELAY_MS.to_be_bytes());
+            fctl.extend_from_slice(&1000u16.to_be_bytes());
+            fctl.extend_from_slice(&[1, 0]); // dispose to background, overwrite
+            write_chunk(out, b"fcTL", &fctl)?;
+            seq += 1;
+        }
+        let data = zlib_stored(&scanlines(image));
+        if i == 0 {
+            write_chunk(out, b"IDAT", &data)?;
+        } else {
+            let mut fdat = seq.to_be_bytes().to_vec();
+            fdat.extend_from_slice(&data);
+            write_chunk(out, b"fdAT", &fdat)?;
+            seq += 1;
+        }
+    }
+
+    write_chunk(out, b"IEND", &[])?;
+    out.flush()
+}
+
+// `image` in the top-left corner of a white `width` x `height` one
+fn padded(image: &Image, width: usize, height: usize) -> Image {
+    let mut pixels = [WHITE.0, WHITE.1, WHITE.2].repeat(width * height);
+    for (row, line) in image
+        .pixels
+        .chunks(image.width * 3)
+        .take(image.height)
+        .enumerate()
+    {
+        let start = row * width * 3;
+        pixels[start..start + line.len()].copy_from_slice(line);
+    }
+    Image {
+        width,
+        height,
+        pixels,
+    }
+}
+
+// each row prefixed with filter type 0
+fn scanlines(image: &Image) -> Vec<u8> {
+    let stride = image.width * 3;
+    let mut raw = Vec::with_capacity((stride + 1) * image.height);
+    for row in image.pixels.chunks(stride.max(1)).take(image.height) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    raw
+}
+
+fn write_chunk(out: &mut dyn Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data.iter()));
+    out.write_all(&crc.to_be_bytes())
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut z = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        z.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        z.push(if blocks.peek().is_none() { 1 } else { 0 });
+        let len = block.len() as u16;
+        z.extend_from_slice(&len.to_le_bytes());
+        z.extend_from_slice(&(!len).to_le_bytes());
+        z.extend_from_slice(block);
+    }
+    z.extend_from_slice(&adler32(data).to_be_bytes());
+    z
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Checkers(usize);
+    impl GridRender for Checkers {
+        fn dimensions(&self) -> (usize, usize) {
+            (self.0, self.0 + 1)
+        }
+        fn cell_color(&self, row: usize, col: usize) -> Rgb {
+            if row % 2 == col % 2 {
+                (0, 0, 0)
+            } else {
+                WHITE
+            }
+        }
+    }
+
+    #[test]
+    fn test_rasterize() {
+        let image = rasterize(&Checkers(2), 2);
+        assert_eq!((image.width, image.height), (6, 4));
+        assert_eq!(image.pixels.len(), 6 * 4 * 3);
+        assert_eq!(&image.pixels[0..3], &[0, 0, 0]);
+        assert_eq!(&image.pixels[6..9], &[0xff, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn test_ppm_header() {
+        let mut out = Vec::new();
+        write_ppm(&rasterize(&Checkers(1), 1), &mut out).unwrap();
+        assert!(out.starts_with(b"P6\n2 1\n255\n"));
+        assert_eq!(out.len(), 11 + 2 * 3);
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND".iter()), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    // (kind, data) of every chunk after the signature
+    fn png_chunks(png: &[u8]) -> Vec<(&[u8], &[u8])> {
+        let mut chunks = Vec::new();
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+            chunks.push((&rest[4..8], &rest[8..8 + len]));
+            rest = &rest[12 + len..];
+        }
+        chunks
+    }
+
+    fn size(data: &[u8]) -> (u32, u32) {
+        let word = |i: usize| u32::from_be_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+        (word(0), word(4))
+    }
+
+    #[test]
+    fn test_png_chunks() {
+        let mut out = Vec::new();
+        let frames = vec![rasterize(&Checkers(2), 1), rasterize(&Checkers(3), 1)];
+        write_png(&frames, &mut out).unwrap();
+        assert!(out.starts_with(&[0x89, b'P', b'N', b'G']));
+        assert!(out.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+
+        // the canvas fits the larger frame, and the first frame covers all of it
+        let chunks = png_chunks(&out);
+        let kinds = chunks.iter().map(|c| c.0).collect::<Vec<&[u8]>>();
+        let expected: [&[u8]; 7] = [
+            b"IHDR", b"acTL", b"fcTL", b"IDAT", b"fcTL", b"fdAT", b"IEND",
+        ];
+        assert_eq!(kinds, expected);
+        assert_eq!(size(chunks[0].1), (4, 3));
+        assert_eq!(size(&chunks[2].1[4..]), (4, 3));
+        assert_eq!(size(&chunks[4].1[4..]), (4, 3));
+
+        let frames = vec![rasterize(&Checkers(3), 1), rasterize(&Checkers(1), 1)];
+        out.clear();
+        write_png(&frames, &mut out).unwrap();
+        let chunks = png_chunks(&out);
+        assert_eq!(size(chunks[0].1), (4, 3));
+        assert_eq!(size(&chunks[4].1[4..]), (2, 1));
+
+        assert!(write_png(&[], &mut Vec::new()).is_err());
+    }
+}
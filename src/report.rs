@@ -0,0 +1,312 @@
+// Runs every registered day and writes up answers, timings, regression checks and stars as
+// Markdown (and optionally HTML).
+use crate::cache::Cache;
+use crate::inputs::Inputs;
+use crate::registry::{self, Outcome, LAST_DAY};
+use std::fs;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Check {
+    Pass,
+    Fail(String), // what the answers file expected
+    Unchecked,
+    Unsolved,
+}
+
+impl Check {
+    fn describe(&self) -> String {
+        match self {
+            Check::Pass => String::from("pass"),
+            Check::Fail(expected) => format!("FAIL (expected {})", expected),
+            Check::Unchecked => String::from("unchecked"),
+            Check::Unsolved => String::from("unsolved"),
+        }
+    }
+}
+
+pub struct DayReport {
+    pub day: usize,
+    pub outcome: Option<Outcome>, // None for days that aren't in the registry yet
+    pub checks: [Check; 2],
+}
+
+impl DayReport {
+    fn answer(&self, part: usize) -> Option<&String> {
+        self.outcome.as_ref().and_then(|o| match part {
+            1 => o.part1.as_ref(),
+            _ => o.part2.as_ref(),
+        })
+    }
+
+    // a star for every part with an answer that isn't known to be wrong
+    pub fn stars(&self) -> usize {
+        (1..=2)
+            .filter(|&part| {
+                self.answer(part).is_some() && !matches!(self.checks[part - 1], Check::Fail(_))
+            })
+            .count()
+    }
+}
+
+fn check(answer: Option<&String>, expected: Option<&String>) -> Check {
+    match (answer, expected) {
+        (None, _) => Check::Unsolved,
+        (Some(_), None) => Check::Unchecked,
+        (Some(a), Some(e)) if a == e => Check::Pass,
+        (Some(_), Some(e)) => Check::Fail(e.clone()),
+    }
+}
+
+pub fn build(
+    outcomes: Vec<(usize, Outcome)>,
+    answers: &[(usize, usize, String)],
+) -> Vec<DayReport> {
+    let expected = |day: usize, part: usize| {
+        answers
+            .iter()
+            .find(|(d, p, _)| *d == day && *p == part)
+            .map(|(_, _, a)| a)
+    };
+
+    (1..=LAST_DAY)
+        .map(|day| {
+            let outcome = outcomes
+                .iter()
+                .find(|(d, _)| *d == day)
+                .map(|(_, o)| o.clone());
+            let checks = match &outcome {
+                Some(o) => [
+                    check(o.part1.as_ref(), expected(day, 1)),
+                    check(o.part2.as_ref(), expected(day, 2)),
+                ],
+                None => [Check::Unsolved, Check::Unsolved],
+            };
+            DayReport {
+                day,
+                outcome,
+                checks,
+            }
+        })
+        .collect()
+}
+
+pub fn format_duration(d: Duration) -> String {
+    let micros = d.as_micros();
+    if micros < 1000 {
+        format!("{} µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.2} ms", micros as f64 / 1000.0)
+    } else {
+        format!("{:.2} s", d.as_secs_f64())
+    }
+}
+
+fn stars_glyph(stars: usize) -> String {
+    format!("{}{}", "★".repeat(stars), "☆".repeat(2 - stars))
+}
+
+// the cells of one answers-table row, shared by both output formats
+fn row_cells(r: &DayReport) -> Vec<String> {
+    let answer = |part| r.answer(part).cloned().unwrap_or_else(|| String::from("-"));
+    let timing = |part, t: fn(&Outcome) -> Duration| match (&r.outcome, r.answer(part)) {
+        (Some(o), Some(_)) => format_duration(t(o)),
+        _ => String::from("-"),
+    };
+    vec![
+        r.day.to_string(),
+        answer(1),
+        answer(2),
+        timing(1, |o| o.parse_time),
+        timing(1, |o| o.part1_time),
+        timing(2, |o| o.part2_time),
+        r.checks[0].describe(),
+        r.checks[1].describe(),
+    ]
+}
+
+const HEADERS: [&str; 8] = [
+    "Day",
+    "Part 1",
+    "Part 2",
+    "Parse",
+    "Part 1 time",
+    "Part 2 time",
+    "Part 1 check",
+    "Part 2 check",
+];
+
+pub fn markdown(reports: &[DayReport]) -> String {
+    let total: usize = reports.iter().map(|r| r.stars()).sum();
+    let mut md = String::from("# Advent of Code 2020\n\n");
+    md += &format!("{} of {} stars\n\n", total, 2 * LAST_DAY);
+
+    md += "## Calendar\n\n";
+    md += "| | | | | |\n|---|---|---|---|---|\n";
+    for week in reports.chunks(5) {
+        let cells = week
+            .iter()
+            .map(|r| format!("{} {}", r.day, stars_glyph(r.stars())))
+            .collect::<Vec<String>>();
+        md += &format!("| {} |\n", cells.join(" | "));
+    }
+
+    md += "\n## Answers\n\n";
+    md += &format!("| {} |\n", HEADERS.join(" | "));
+    md += "|--:|---|---|--:|--:|--:|---|---|\n";
+    for r in reports {
+        md += &format!("| {} |\n", row_cells(r).join(" | "));
+    }
+    md
+}
+
+pub fn html(reports: &[DayReport]) -> String {
+    let total: usize = reports.iter().map(|r| r.stars()).sum();
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Advent of Code 2020</title></head>\n<body>\n",
+    );
+    html += "<h1>Advent of Code 2020</h1>\n";
+    html += &format!("<p>{} of {} stars</p>\n", total, 2 * LAST_DAY);
+
+    html += "<h2>Calendar</h2>\n<table>\n";
+    for week in reports.chunks(5) {
+        html += "<tr>";
+        for r in week {
+            html += &format!("<td>{} {}</td>", r.day, stars_glyph(r.stars()));
+        }
+        html += "</tr>\n";
+    }
+    html += "</table>\n";
+
+    html += "<h2>Answers</h2>\n<table>\n<tr>";
+    for h in HEADERS.iter() {
+        html += &format!("<th>{}</th>", h);
+    }
+    html += "</tr>\n";
+    for r in reports {
+        html += "<tr>";
+        for cell in row_cells(r) {
+            html += &format!("<td>{}</td>", escape(&cell));
+        }
+        html += "</tr>\n";
+    }
+    html += "</table>\n</body>\n</html>\n";
+    html
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// `cache`, if given, supplies answers from earlier runs and is updated with new ones
+pub fn report(
+    inputs: &Inputs,
+    mut cache: Option<&mut Cache>,
+    output: &str,
+    html_output: Option<&str>,
+    answers_file: &str,
+) -> std::io::Result<()> {
+    let answers = match fs::read_to_string(answers_file) {
+        Ok(s) => registry::parse_answers(&s),
+        Err(e) => {
+            eprintln!(
+                "Not checking answers, couldn't read {}: {}",
+                answers_file, e
+            );
+            Vec::new()
+        }
+    };
+
+    let outcomes = registry::days()
+        .iter()
+        .filter_map(|d| match inputs.get(d.day) {
+            Ok(input) => match cache
+                .as_deref()
+                .and_then(|c| c.get(d.day, &input, &registry::settings(d.day)))
+            {
+                Some(outcome) => {
+                    eprintln!("Day {} is cached", d.day);
+                    Some((d.day, outcome))
+                }
+                None => {
+                    eprintln!("Running day {}", d.day);
+                    let outcome = (d.solve)(&input);
+                    if let Some(cache) = cache.as_deref_mut() {
+                        cache.insert(d.day, &input, &registry::settings(d.day), &outcome);
+                    }
+                    Some((d.day, outcome))
+                }
+            },
+            Err(e) => {
+                eprintln!("Skipping day {}: {}", d.day, e);
+                None
+            }
+        })
+        .collect::<Vec<(usize, Outcome)>>();
+    if let Some(cache) = cache {
+        cache.save()?;
+    }
+    let reports = build(outcomes, &answers);
+
+    fs::write(output, markdown(&reports))?;
+    println!("Wrote {}", output);
+    if let Some(html_output) = html_output {
+        fs::write(html_output, html(&reports))?;
+        println!("Wrote {}", html_output);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn outcome(part1: &str, part2: Option<&str>) -> Outcome {
+        Outcome {
+            part1: Some(String::from(part1)),
+            part2: part2.map(String::from),
+            parse_time: Duration::from_micros(5),
+            part1_time: Duration::from_millis(2),
+            part2_time: Duration::from_secs(3),
+        }
+    }
+
+    #[test]
+    fn test_build() {
+        let answers = vec![(1, 1, String::from("7")), (1, 2, String::from("8"))];
+        let reports = build(
+            vec![(1, outcome("7", Some("9"))), (2, outcome("1", None))],
+            &answers,
+        );
+        assert_eq!(reports.len(), 25);
+        assert_eq!(
+            reports[0].checks,
+            [Check::Pass, Check::Fail(String::from("8"))]
+        );
+        assert_eq!(reports[0].stars(), 1);
+        assert_eq!(reports[1].checks, [Check::Unchecked, Check::Unsolved]);
+        assert_eq!(reports[1].stars(), 1);
+        assert_eq!(reports[24].checks, [Check::Unsolved, Check::Unsolved]);
+        assert_eq!(reports[24].stars(), 0);
+    }
+
+    #[test]
+    fn test_markdown() {
+        let reports = build(vec![(1, outcome("7", Some("9")))], &[]);
+        let md = markdown(&reports);
+        assert!(md.contains("2 of 50 stars"));
+        assert!(md.contains("| 1 ★★ | 2 ☆☆ |"));
+        assert!(md.contains("| 1 | 7 | 9 | 5 µs | 2.00 ms | 3.00 s | unchecked | unchecked |"));
+        assert!(md.contains("| 25 | - | - | - | - | - | unsolved | unsolved |"));
+    }
+
+    #[test]
+    fn test_html() {
+        let reports = build(vec![(1, outcome("<7>", None))], &[]);
+        let html = html(&reports);
+        assert!(html.contains("<td>&lt;7&gt;</td>"));
+        assert!(html.contains("<p>1 of 50 stars</p>"));
+    }
+}
@@ -0,0 +1,302 @@
+// Runs a day's solver on generated inputs of growing size and fits how its time grows, to check
+// hunches like "day 14 part 2 is exponential in the floating bits". Each day with a generator
+// says what its size means; the same seed always gives the same inputs.
+use crate::days::day16;
+use crate::registry::{self, Outcome};
+use crate::report::format_duration;
+use crate::{day10, day14, day2, day3, day9};
+use std::thread;
+use std::time::Duration;
+
+// xorshift64*: tiny, seedable, and good enough for making up puzzle inputs
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed.max(1)) // xorshift gets stuck on zero
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    // uniform-ish in lo..hi; panics if the range is empty
+    pub fn range(&mut self, lo: usize, hi: usize) -> usize {
+        assert!(lo < hi, "empty range {}..{}", lo, hi);
+        lo + (self.next_u64() % (hi - lo) as u64) as usize
+    }
+
+    pub fn chance(&mut self, percent: usize) -> bool {
+        self.range(0, 100) < percent
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.range(0, i + 1));
+        }
+    }
+}
+
+// how a day's time is expected to grow with its size, which decides the curve that's fitted
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Growth {
+    Power,       // c·n^k
+    Exponential, // c·b^n
+}
+
+pub struct Generator {
+    pub day: usize,
+    pub size: &'static str, // what the size counts
+    pub sizes: &'static [usize],
+    pub growth: Growth,
+    pub generate: fn(usize, &mut Rng) -> String,
+}
+
+pub fn generators() -> Vec<Generator> {
+    vec![
+        Generator {
+            day: 2,
+            size: "password lines",
+            sizes: &[1000, 2000, 4000, 8000, 16000],
+            growth: Growth::Power,
+            generate: day2::generate,
+        },
+        Generator {
+            day: 3,
+            size: "map rows",
+            sizes: &[125_000, 250_000, 500_000, 1_000_000, 2_000_000],
+            growth: Growth::Power,
+            generate: day3::generate,
+        },
+        Generator {
+            day: 9,
+            size: "numbers in the list",
+            sizes: &[1000, 2000, 4000, 8000, 16000],
+            growth: Growth::Power,
+            generate: day9::generate,
+        },
+        Generator {
+            day: 10,
+            size: "adapters",
+            sizes: &[250, 500, 1000, 2000, 4000],
+            growth: Growth::Power,
+            generate: day10::generate,
+        },
+        Generator {
+            day: 14,
+            size: "floating bits per mask",
+            sizes: &[2, 4, 6, 8, 10, 12],
+            growth: Growth::Exponential,
+            generate: day14::generate,
+        },
+        Generator {
+            day: 16,
+            size: "nearby tickets",
+            sizes: &[250, 500, 1000, 2000, 4000],
+            growth: Growth::Power,
+            generate: day16::generate,
+        },
+    ]
+}
+
+// Least-squares fit of ln(time) against ln(size): (exponent, r²) for time ≈ c·size^exponent.
+// None with fewer than two usable points.
+pub fn fit_exponent(points: &[(usize, Duration)]) -> Option<(f64, f64)> {
+    let logs = points
+        .iter()
+        .filter(|(size, time)| *size > 0 && time.as_nanos() > 0)
+        .map(|(size, time)| ((*size as f64).ln(), time.as_secs_f64().ln()))
+        .collect::<Vec<(f64, f64)>>();
+    least_squares(&logs)
+}
+
+// Least-squares fit of ln(time) against size: (base, r²) for time ≈ c·base^size.
+pub fn fit_base(points: &[(usize, Duration)]) -> Option<(f64, f64)> {
+    let logs = points
+        .iter()
+        .filter(|(_, time)| time.as_nanos() > 0)
+        .map(|(size, time)| (*size as f64, time.as_secs_f64().ln()))
+        .collect::<Vec<(f64, f64)>>();
+    least_squares(&logs).map(|(slope, r2)| (slope.exp(), r2))
+}
+
+// (slope, r²) of the line through (x, y) points
+fn least_squares(logs: &[(f64, f64)]) -> Option<(f64, f64)> {
+    if logs.len() < 2 {
+        return None;
+    }
+    let n = logs.len() as f64;
+    let mean_x = logs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = logs.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx = logs.iter().map(|p| (p.0 - mean_x).powi(2)).sum::<f64>();
+    let sxy = logs
+        .iter()
+        .map(|p| (p.0 - mean_x) * (p.1 - mean_y))
+        .sum::<f64>();
+    let syy = logs.iter().map(|p| (p.1 - mean_y).powi(2)).sum::<f64>();
+    if sxx == 0.0 {
+        return None;
+    }
+    let slope = sxy / sxx;
+    let r2 = if syy == 0.0 {
+        1.0
+    } else {
+        (sxy * sxy) / (sxx * syy)
+    };
+    Some((slope, r2))
+}
+
+fn describe_fit(points: &[(usize, Duration)], growth: Growth) -> String {
+    match growth {
+        Growth::Power => {
+            fit_exponent(points).map(|(exponent, r2)| format!("n^{:.2} (r² {:.2})", exponent, r2))
+        }
+        Growth::Exponential => {
+            fit_base(points).map(|(base, r2)| format!("{:.2}^n (r² {:.2})", base, r2))
+        }
+    }
+    .unwrap_or_else(|| String::from("-"))
+}
+
+// fastest of `runs` runs, per phase
+fn measure(solve: fn(&str) -> Outcome, input: &str, runs: usize) -> [Duration; 3] {
+    let outcomes = (0..runs.max(1))
+        .map(|_| solve(input))
+        .collect::<Vec<Outcome>>();
+    let fastest = |t: fn(&Outcome) -> Duration| outcomes.iter().map(t).min().unwrap();
+    [
+        fastest(|o| o.parse_time),
+        fastest(|o| o.part1_time),
+        fastest(|o| o.part2_time),
+    ]
+}
+
+pub fn scale(day: usize, sizes: Option<Vec<usize>>, seed: u64, runs: usize) {
+    let generator = match generators().into_iter().find(|g| g.day == day) {
+        Some(g) => g,
+        None => {
+            let days = generators()
+                .iter()
+                .map(|g| g.day.to_string())
+                .collect::<Vec<String>>();
+            println!(
+                "No input generator for day {}; try one of {}",
+                day,
+                days.join(", ")
+            );
+            return;
+        }
+    };
+    let solve = match registry::days().into_iter().find(|d| d.day == day) {
+        Some(d) => d.solve,
+        None => {
+            println!("No solution registered for day {}", day);
+            return;
+        }
+    };
+    let sizes = sizes.unwrap_or_else(|| generator.sizes.to_vec());
+
+    println!(
+        "day {}, size = {}, seed {}, best of {}",
+        day, generator.size, seed, runs
+    );
+    println!(
+        "{:>10} {:>12} {:>12} {:>12}",
+        "size", "parse", "part 1", "part 2"
+    );
+    let mut points: [Vec<(usize, Duration)>; 3] = Default::default();
+    for &size in &sizes {
+        let input = (generator.generate)(size, &mut Rng::new(seed));
+        // the badly scaling solvers tend to be the deeply recursive ones too
+        let times = thread::Builder::new()
+            .stack_size(256 << 20)
+            .spawn(move || measure(solve, &input, runs))
+            .and_then(|t| {
+                t.join()
+                    .map_err(|_| std::io::Error::other("solver panicked"))
+            });
+        let times = match times {
+            Ok(times) => times,
+            Err(e) => {
+                println!("{:>10} {}", size, e);
+                break;
+            }
+        };
+        println!(
+            "{:>10} {:>12} {:>12} {:>12}",
+            size,
+            format_duration(times[0]),
+            format_duration(times[1]),
+            format_duration(times[2])
+        );
+        for (phase, &time) in points.iter_mut().zip(times.iter()) {
+            phase.push((size, time));
+        }
+    }
+    println!(
+        "growth: parse {}, part 1 {}, part 2 {}",
+        describe_fit(&points[0], generator.growth),
+        describe_fit(&points[1], generator.growth),
+        describe_fit(&points[2], generator.growth)
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        let xs = (0..5).map(|_| a.range(0, 10)).collect::<Vec<usize>>();
+        assert_eq!(xs, (0..5).map(|_| b.range(0, 10)).collect::<Vec<usize>>());
+        assert!(xs.iter().all(|&x| x < 10));
+        let mut items = [1, 2, 3, 4, 5];
+        a.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_fit_exponent() {
+        let quadratic = [10, 20, 40, 80]
+            .iter()
+            .map(|&n| (n, Duration::from_nanos((n * n) as u64 * 100)))
+            .collect::<Vec<(usize, Duration)>>();
+        let (exponent, r2) = fit_exponent(&quadratic).unwrap();
+        assert!((exponent - 2.0).abs() < 1e-6);
+        assert!((r2 - 1.0).abs() < 1e-6);
+        assert_eq!(fit_exponent(&quadratic[..1]), None);
+
+        let doubling = [2, 4, 6, 8]
+            .iter()
+            .map(|&n| (n, Duration::from_nanos(1000 << n)))
+            .collect::<Vec<(usize, Duration)>>();
+        let (base, r2) = fit_base(&doubling).unwrap();
+        assert!((base - 2.0).abs() < 1e-6);
+        assert!((r2 - 1.0).abs() < 1e-6);
+        assert_eq!(
+            describe_fit(&doubling, Growth::Exponential),
+            "2.00^n (r² 1.00)"
+        );
+    }
+
+    #[test]
+    fn test_generators() {
+        // every generated input is one its solver can finish on
+        for g in generators() {
+            let day = registry::days()
+                .into_iter()
+                .find(|d| d.day == g.day)
+                .unwrap();
+            let input = (g.generate)(g.sizes[0], &mut Rng::new(1));
+            let outcome = (day.solve)(&input);
+            assert!(outcome.part1.is_some(), "day {}", g.day);
+            assert_ne!(outcome.part1.as_deref(), Some("none"), "day {}", g.day);
+        }
+    }
+}